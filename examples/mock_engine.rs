@@ -0,0 +1,58 @@
+//! A tiny engine that plays whatever `Game::hint` suggests, for trying out
+//! the engine protocol (see the `engine` module) and for testing.
+//!
+//! Run it with `cargo run --example mock_engine`, or play against it with
+//! `nd-tic-tac-toe -d 2 -p 2 -e O=target/debug/examples/mock_engine`. With
+//! `--delay <milliseconds>`, it waits that long before each move, like an
+//! engine that thinks for too long.
+
+use std::{
+    io::{self, BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+use nd_tic_tac_toe::protocol::replay_state;
+
+fn main() -> io::Result<()> {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let delay = match args.as_slice() {
+        [] => Duration::ZERO,
+        [flag, millis] if flag == "--delay" => {
+            Duration::from_millis(millis.parse().expect("Invalid delay"))
+        }
+        _ => panic!("Usage: mock_engine [--delay <milliseconds>]"),
+    };
+
+    let mut output = io::stdout();
+    let mut size = None;
+    let mut moves = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words: Vec<_> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["newgame", dim, _, players] => {
+                size = Some((dim.parse().unwrap(), players.parse().unwrap()))
+            }
+            ["position", "moves", played @ ..] => {
+                moves = played
+                    .iter()
+                    .map(|coords| coords.split(',').map(|x| x.parse().unwrap()).collect())
+                    .collect()
+            }
+            ["go", "movetime", _] => {
+                let (dim, players) = size.expect("A move was asked for before a game");
+                let game = replay_state(dim, players, &moves).expect("Illegal moves");
+                let coords = game.hint(game.current_player()).expect("The board is full");
+                let coords = game.map_player_coords(coords);
+                thread::sleep(delay);
+                writeln!(output, "info thinking")?;
+                writeln!(output, "bestmove {}", itertools::join(coords, " "))?;
+                output.flush()?;
+            }
+            ["quit"] => break,
+            _ => panic!("Unexpected message `{line}`"),
+        }
+    }
+    Ok(())
+}
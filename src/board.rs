@@ -1,5 +1,5 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fmt::Display,
     ops::{Index, IndexMut},
 };
@@ -87,6 +87,28 @@ impl<T> Board<T> {
         })
     }
 
+    /// Takes the part of the board where the coordinates in `fixed` that are
+    /// `Some` have that value. The axes that are `None` are kept, in the same
+    /// order. Returns `None` if any of the coordinates are out of bounds.
+    pub fn slice(&self, fixed: &[Option<usize>]) -> Option<Board<T>>
+    where
+        T: Clone,
+    {
+        let Some((first, rest)) = fixed.split_first() else {
+            return Some(self.clone());
+        };
+
+        match (self, first) {
+            (Board::Nd(boards), Some(i)) => boards.get(*i)?.slice(rest),
+            (Board::Nd(boards), None) => boards
+                .iter()
+                .map(|board| board.slice(rest))
+                .collect::<Option<_>>()
+                .map(Board::Nd),
+            (Board::Piece(_), _) => None,
+        }
+    }
+
     pub fn flatten(&self) -> Vec<&T> {
         match self {
             Board::Nd(boards) => boards.iter().fold(vec![], |mut acc, e| {
@@ -125,6 +147,16 @@ pub(crate) enum Direction {
 }
 
 impl Direction {
+    /// The direction of the outermost level is horizontal, and they alternate
+    /// from there.
+    pub(crate) fn of_level(level: usize) -> Self {
+        if level.is_multiple_of(2) {
+            Direction::Horizontal
+        } else {
+            Direction::Vertical
+        }
+    }
+
     fn next(&self) -> Self {
         match self {
             Direction::Horizontal => Direction::Vertical,
@@ -166,23 +198,344 @@ fn combine_multiline_strings(strings: Vec<String>, direction: Direction, sep: &s
     }
 }
 
+/// A way of drawing attention to a cell when displaying a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    WinningLine,
+    LastMove,
+    /// An empty cell the next move has to be made in, like the cells of the
+    /// small board a player was sent to in ultimate tic-tac-toe.
+    Target,
+}
+
+impl Highlight {
+    /// Styles an already displayed piece.
+    fn apply(&self, piece: String, plain: bool) -> String {
+        // The piece resets the style after itself, so the highlight only needs
+        // to be turned on.
+        match (self, plain) {
+            // Reverse video
+            (Highlight::WinningLine, false) => format!("\x1b[7m{piece}\x1b[0m"),
+            // Underline
+            (Highlight::LastMove, false) => format!("\x1b[4m{piece}\x1b[0m"),
+            // Green background
+            (Highlight::Target, false) => format!("\x1b[42m{piece}\x1b[0m"),
+            // Without escape codes, the best that can be done is changing the
+            // case of the symbol.
+            (Highlight::WinningLine, true) => piece.to_lowercase(),
+            (Highlight::LastMove, true) => piece,
+            (Highlight::Target, true) => piece.replace('.', "+"),
+        }
+    }
+}
+
 impl<T: Display + std::fmt::Debug> Board<T> {
     pub(crate) fn display(&self, direction: Direction, hide_padding: bool) -> String {
+        self.display_with(direction, hide_padding, &HashMap::new(), false)
+    }
+
+    /// Like [`Board::display`], but with some pieces highlighted. If `plain`
+    /// is true, the pieces are displayed with their alternate form (`{:#}`),
+    /// and no escape codes are added.
+    pub(crate) fn display_with(
+        &self,
+        direction: Direction,
+        hide_padding: bool,
+        highlights: &HashMap<Idx, Highlight>,
+        plain: bool,
+    ) -> String {
+        self.display_inner(direction, hide_padding, highlights, plain, &mut Idx::new())
+    }
+
+    fn display_inner(
+        &self,
+        direction: Direction,
+        hide_padding: bool,
+        highlights: &HashMap<Idx, Highlight>,
+        plain: bool,
+        coords: &mut Idx,
+    ) -> String {
         let sep = if hide_padding { "" } else { " " };
         match self {
             Board::Nd(boards) => {
-                let boards = boards
-                    .iter()
-                    .map(|board| board.display(direction.next(), hide_padding));
+                let boards = boards.iter().enumerate().map(|(i, board)| {
+                    coords.push_back(i);
+                    let string = board.display_inner(
+                        direction.next(),
+                        hide_padding,
+                        highlights,
+                        plain,
+                        coords,
+                    );
+                    coords.pop_back();
+                    string
+                });
                 combine_multiline_strings(boards.collect(), direction, sep)
             }
-            Board::Piece(piece) => piece.to_string(),
+            Board::Piece(piece) => {
+                let string = if plain {
+                    format!("{piece:#}")
+                } else {
+                    piece.to_string()
+                };
+                match highlights.get(coords) {
+                    Some(highlight) => highlight.apply(string, plain),
+                    None => string,
+                }
+            }
+        }
+    }
+}
+
+/// Where the pieces of a board end up in the output of its `display` method.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    /// The length of the longest line.
+    pub width: usize,
+    /// The number of lines.
+    pub height: usize,
+    /// The coordinates of each piece along with its `(line, column)`, in the
+    /// same order as [`Board::flatten`].
+    pub cells: Vec<(Idx, (usize, usize))>,
+}
+
+impl Layout {
+    pub fn position(&self, coords: &Idx) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .find(|(c, _)| c == coords)
+            .map(|(_, position)| *position)
+    }
+
+    pub fn cell_at(&self, line: usize, column: usize) -> Option<&Idx> {
+        self.cells
+            .iter()
+            .find(|(_, position)| *position == (line, column))
+            .map(|(coords, _)| coords)
+    }
+
+    /// Moves every piece down by `lines` and right by `columns`.
+    pub(crate) fn offset(self, lines: usize, columns: usize) -> Layout {
+        Layout {
+            width: self.width + columns,
+            height: self.height + lines,
+            cells: self
+                .cells
+                .into_iter()
+                .map(|(coords, (line, column))| (coords, (line + lines, column + columns)))
+                .collect(),
+        }
+    }
+
+    /// Works out the coordinate labels for the board (which was laid out
+    /// starting in `direction`), naming the axis of each level with `names`.
+    ///
+    /// The horizontal levels are labelled above the board, from the outermost
+    /// down, with their name at the end of the line. The vertical levels are
+    /// labelled in columns to the left of the board, with their names above.
+    pub(crate) fn labels(&self, names: &[String], direction: Direction) -> Labels {
+        // The outermost level goes in `direction`, and they alternate from there.
+        let (horizontal, vertical): (Vec<_>, Vec<_>) = (0..names.len())
+            .partition(|level| (level % 2 == 0) == (direction == Direction::Horizontal));
+
+        let label_width = self
+            .cells
+            .iter()
+            .flat_map(|(coords, _)| coords.iter())
+            .max()
+            .map_or(1, |max| max.to_string().len())
+            .max(names.iter().map(|name| name.len()).max().unwrap_or(0));
+        let margin = vertical.len() * (label_width + 1);
+
+        // Lines are built out of chars so the labels can be placed by column
+        let mut header = vec![vec![' '; margin + self.width]; horizontal.len()];
+        let mut left = vec![vec![' '; margin]; self.height];
+
+        // Columns only depend on the horizontal coordinates, and lines only on
+        // the vertical ones.
+        for (i, &level) in horizontal.iter().enumerate() {
+            for (value, (first, last)) in self.spans(level, &horizontal, |(_, column)| column) {
+                let label = value.to_string();
+                let start = (margin + (first + last) / 2).saturating_sub((label.len() - 1) / 2);
+                place(&mut header[i], start, &label);
+            }
+            header[i].extend(format!(" {}", names[level]).chars());
+        }
+        for (i, &level) in vertical.iter().enumerate() {
+            let end = (i + 1) * (label_width + 1) - 1;
+            for (value, (first, last)) in self.spans(level, &vertical, |(line, _)| line) {
+                let label = value.to_string();
+                place(
+                    &mut left[(first + last) / 2],
+                    end.saturating_sub(label.len()),
+                    &label,
+                );
+            }
+            if let Some(last) = header.last_mut() {
+                place(last, end.saturating_sub(names[level].len()), &names[level]);
+            }
+        }
+
+        Labels {
+            header: header.into_iter().map(String::from_iter).collect(),
+            left: left.into_iter().map(String::from_iter).collect(),
+            margin,
+        }
+    }
+
+    /// Groups the pieces by their coordinates in `levels` (up to and
+    /// including `level`), and finds the range of lines or columns (picked out
+    /// by `position`) each group takes up, along with its coordinate in
+    /// `level`.
+    fn spans(
+        &self,
+        level: usize,
+        levels: &[usize],
+        position: impl Fn((usize, usize)) -> usize,
+    ) -> Vec<(usize, (usize, usize))> {
+        let mut spans: HashMap<Vec<usize>, (usize, (usize, usize))> = HashMap::new();
+        for (coords, pos) in &self.cells {
+            let key = levels
+                .iter()
+                .take_while(|l| **l <= level)
+                .map(|l| coords[*l])
+                .collect();
+            let pos = position(*pos);
+            let span = spans.entry(key).or_insert((coords[level], (pos, pos)));
+            span.1 = (span.1 .0.min(pos), span.1 .1.max(pos));
+        }
+        spans.into_values().collect()
+    }
+}
+
+/// Overwrites part of `line` with `text`, starting at `start`. Whatever
+/// doesn't fit on the line is cut off.
+fn place(line: &mut [char], start: usize, text: &str) {
+    for (slot, c) in line.iter_mut().skip(start).zip(text.chars()) {
+        *slot = c;
+    }
+}
+
+/// The name of an axis, numbered in the order players enter coordinates in
+/// (from 0). These are letters, and then `x` and the axis's number (from 1)
+/// once they run out, the same as players can type them.
+pub(crate) fn axis_name(axis: usize) -> String {
+    match u8::try_from(axis) {
+        Ok(axis @ 0..26) => char::from(b'a' + axis).to_string(),
+        _ => format!("x{}", axis + 1),
+    }
+}
+
+/// Coordinate labels to go around a displayed board.
+pub(crate) struct Labels {
+    /// Lines to go above the board.
+    header: Vec<String>,
+    /// What to put to the left of each line of the board.
+    left: Vec<String>,
+    /// The width of `left`.
+    margin: usize,
+}
+
+impl Labels {
+    pub(crate) fn apply(&self, display: &str) -> String {
+        let board = display
+            .lines()
+            .zip(&self.left)
+            .map(|(line, left)| format!("{left}{line}"));
+        self.header
+            .iter()
+            .cloned()
+            .chain(board)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Every (non-space) character of the labels, with its `(line, column)`
+    /// once the labels are added.
+    pub(crate) fn chars(&self) -> impl Iterator<Item = ((usize, usize), char)> + '_ {
+        let header_lines = self.header.len();
+        let header = self.header.iter().enumerate();
+        let left = self
+            .left
+            .iter()
+            .enumerate()
+            .map(move |(i, line)| (header_lines + i, line));
+
+        header
+            .chain(left)
+            .flat_map(|(i, line)| line.chars().enumerate().map(move |(j, c)| ((i, j), c)))
+            .filter(|(_, c)| *c != ' ')
+    }
+
+    /// Moves a layout to where the pieces will be once the labels are added.
+    pub(crate) fn offset(&self, layout: Layout) -> Layout {
+        layout.offset(self.header.len(), self.margin)
+    }
+}
+
+impl<T> Board<T> {
+    /// Mirrors [`Board::display`] (and `combine_multiline_strings`), but
+    /// works out where each piece goes instead of building the string.
+    pub(crate) fn layout(&self, direction: Direction, hide_padding: bool) -> Layout {
+        let sep = if hide_padding { 0 } else { 1 };
+        let Board::Nd(boards) = self else {
+            return Layout {
+                width: 1,
+                height: 1,
+                cells: vec![(Idx::new(), (0, 0))],
+            };
+        };
+
+        let layouts = boards
+            .iter()
+            .map(|board| board.layout(direction.next(), hide_padding));
+
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        match direction {
+            Direction::Horizontal => {
+                // Every line is surrounded by a space, and the boards are
+                // separated by `sep`.
+                let mut column = 1;
+                for (i, layout) in layouts.enumerate() {
+                    cells.extend(layout.cells.into_iter().map(|(mut coords, (l, c))| {
+                        coords.push_front(i);
+                        (coords, (l, column + c))
+                    }));
+                    column += layout.width + sep;
+                    height = layout.height;
+                }
+                width = column - sep + 1;
+            }
+            Direction::Vertical => {
+                // There is an empty line before the first board.
+                let mut line = 1;
+                for (i, layout) in layouts.enumerate() {
+                    cells.extend(layout.cells.into_iter().map(|(mut coords, (l, c))| {
+                        coords.push_front(i);
+                        (coords, (line + l, c))
+                    }));
+                    line += layout.height;
+                    width = width.max(layout.width);
+                }
+                height = line;
+            }
+        }
+
+        Layout {
+            width,
+            height,
+            cells,
         }
     }
 }
 
+/// The alternate form (`{:#}`) uses the alternate form of the pieces.
 impl<T: Display + std::fmt::Debug> Display for Board<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.display(Direction::Horizontal, false).as_str())
+        let display =
+            self.display_with(Direction::Horizontal, false, &HashMap::new(), f.alternate());
+        f.write_str(display.as_str())
     }
 }
@@ -0,0 +1,211 @@
+//! Playing a game hosted by `nd-tic-tac-toe server` on another machine.
+
+use std::{
+    io::{self, BufRead, BufReader, Lines, Write},
+    net::TcpStream,
+};
+
+use nd_tic_tac_toe::{
+    protocol::{self, ClientMessage, ServerMessage},
+    DisplayOptions, Game, GameOutcome, Player,
+};
+use rustyline::error::ReadlineError;
+
+use crate::input::{Command, Input};
+
+/// Which seat to take on the server.
+pub enum Seat {
+    /// The first free seat in any game
+    First,
+    Game(u32),
+    /// A seat in a new game
+    New {
+        dim: usize,
+        players: u32,
+    },
+    /// The seat taken earlier with `token`
+    Rejoin {
+        game: u32,
+        token: u64,
+    },
+    /// No seat, just watching the game
+    Spectate(u32),
+}
+
+struct Connection {
+    writer: TcpStream,
+    lines: Lines<BufReader<TcpStream>>,
+}
+
+impl Connection {
+    fn open(address: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        Ok(Self {
+            writer: stream.try_clone()?,
+            lines: BufReader::new(stream).lines(),
+        })
+    }
+
+    fn send(&mut self, message: ClientMessage) -> io::Result<()> {
+        writeln!(self.writer, "{message}")
+    }
+
+    /// Returns `None` once the server hangs up.
+    fn receive(&mut self) -> io::Result<Option<ServerMessage>> {
+        let Some(line) = self.lines.next().transpose()? else {
+            return Ok(None);
+        };
+        line.parse::<ServerMessage>()
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+/// Prints the games being hosted at `address`.
+pub fn list(address: &str) -> io::Result<()> {
+    let mut connection = Connection::open(address)?;
+    connection.send(ClientMessage::List)?;
+
+    while let Some(message) = connection.receive()? {
+        if let ServerMessage::Games(games) = message {
+            if games.is_empty() {
+                println!("No games are being hosted");
+            }
+            for info in games {
+                println!(
+                    "Game {}: {} dimensions, {} players, {} free seats, {} spectators",
+                    info.id, info.dim, info.players, info.open_seats, info.spectators
+                );
+            }
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Takes `seat` in a game at `address` and plays it out (or watches it),
+/// showing the board with `options` (whose highlights are replaced).
+pub fn run(address: &str, seat: Seat, options: DisplayOptions) -> io::Result<()> {
+    let mut connection = Connection::open(address)?;
+    let mut input = Input::new().map_err(io::Error::other)?;
+
+    match seat {
+        Seat::First => connection.send(ClientMessage::Join(None))?,
+        Seat::Game(game) => connection.send(ClientMessage::Join(Some(game)))?,
+        Seat::New { dim, players } => connection.send(ClientMessage::Create { dim, players })?,
+        Seat::Rejoin { game, token } => connection.send(ClientMessage::Rejoin { game, token })?,
+        Seat::Spectate(game) => connection.send(ClientMessage::Spectate(game))?,
+    }
+
+    let name = |player: Player| {
+        if options.plain {
+            format!("{player:#}")
+        } else {
+            player.to_string()
+        }
+    };
+    // Who we're playing as (if we aren't spectating), and the size of the
+    // game, once we're in one
+    let mut me = None;
+    let mut size = None;
+    let mut game: Option<Game> = None;
+    while let Some(message) = connection.receive()? {
+        match message {
+            ServerMessage::Created(id) => {
+                connection.send(ClientMessage::Join(Some(id)))?;
+                continue;
+            }
+            ServerMessage::Join {
+                player,
+                dim,
+                players,
+                game: id,
+                token,
+            } => {
+                me = Some(player);
+                size = Some((dim, players));
+                println!(
+                    "Joined game {id} as {} (if you get disconnected, rejoin with `--rejoin {id}:{token:x}`)",
+                    name(player)
+                );
+                continue;
+            }
+            ServerMessage::Spectate {
+                game: id,
+                dim,
+                players,
+            } => {
+                size = Some((dim, players));
+                println!("Watching game {id}");
+                continue;
+            }
+            ServerMessage::State(moves) => {
+                let Some((dim, players)) = size else {
+                    continue;
+                };
+                let state = protocol::replay_state(dim, players, &moves)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+                if !options.plain {
+                    print!("\x1B[2J\x1B[1;1H");
+                }
+                println!(
+                    "{}\n",
+                    state.display_with(&DisplayOptions {
+                        highlights: state.highlights(),
+                        ..options.clone()
+                    })
+                );
+                game = Some(state);
+            }
+            // Couldn't get into the game
+            ServerMessage::Error(message) if size.is_none() => {
+                return Err(io::Error::other(message));
+            }
+            ServerMessage::Error(message) => println!("{message}"),
+            ServerMessage::Result(GameOutcome::Win(player)) => {
+                println!("{} wins!", name(player));
+                break;
+            }
+            ServerMessage::Result(GameOutcome::Draw) => {
+                println!("It's a draw!");
+                break;
+            }
+            ServerMessage::Games(_) => continue,
+        }
+
+        // Only ask for a move when it's our turn (including after a move was
+        // rejected)
+        let Some(game) = &game else {
+            continue;
+        };
+        if game.outcome().is_some() {
+            continue;
+        }
+        let Some(player) = me.filter(|player| *player == game.current_player()) else {
+            println!("Waiting for {}...", name(game.current_player()));
+            continue;
+        };
+
+        println!("{}: your move", name(player));
+        loop {
+            let line = match input.read_line() {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => return Ok(()),
+                Err(e) => return Err(io::Error::other(e)),
+            };
+            match line.parse() {
+                Ok(Command::Move(coords)) => {
+                    connection.send(ClientMessage::Move(coords))?;
+                    break;
+                }
+                Ok(Command::Quit) => return Ok(()),
+                Ok(_) => println!("Only moves and `quit` work in online games"),
+                Err(e) => println!("{e}"),
+            }
+        }
+    }
+
+    println!("Game over. Goodbye!");
+    Ok(())
+}
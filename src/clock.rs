@@ -0,0 +1,142 @@
+//! Keeping track of how long players take to move.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use super::Player;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeControl {
+    /// Each player gets `total` for the whole game, plus `increment` after
+    /// each of their moves.
+    Increment {
+        total: Duration,
+        increment: Duration,
+    },
+    /// Each player gets the same amount of time for every move.
+    PerMove(Duration),
+}
+
+/// A clock for each player, with only the current player's running.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: Vec<(Player, Duration)>,
+    running: Option<(Player, Instant)>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, players: &[Player]) -> Self {
+        let time = match control {
+            TimeControl::Increment { total, .. } => total,
+            TimeControl::PerMove(time) => time,
+        };
+        Self {
+            control,
+            remaining: players.iter().map(|player| (*player, time)).collect(),
+            running: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// How long `player` has left (for their current move, with
+    /// [`TimeControl::PerMove`]), counting the time on their clock if it's
+    /// running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `player` doesn't have a clock.
+    pub fn remaining(&self, player: Player) -> Duration {
+        let (_, remaining) = self.remaining.iter().find(|(p, _)| *p == player).unwrap();
+        match self.running {
+            Some((running, started)) if running == player => {
+                remaining.saturating_sub(started.elapsed())
+            }
+            _ => *remaining,
+        }
+    }
+
+    /// Changes how long `player` has left, like when resuming a saved game.
+    pub fn set_remaining(&mut self, player: Player, time: Duration) {
+        if let Some((_, remaining)) = self.remaining.iter_mut().find(|(p, _)| *p == player) {
+            *remaining = time;
+        }
+    }
+
+    /// Starts `player`'s clock, if it isn't already running.
+    pub fn start(&mut self, player: Player) {
+        if self.running.is_none_or(|(running, _)| running != player) {
+            self.stop_running();
+            self.running = Some((player, Instant::now()));
+        }
+    }
+
+    /// Stops the running clock after a move, adding the increment (or
+    /// resetting it for the next move).
+    pub fn stop(&mut self) {
+        let Some((player, _)) = self.running else {
+            return;
+        };
+        self.stop_running();
+        let remaining = self.remaining(player);
+        let next = match self.control {
+            TimeControl::Increment { increment, .. } => remaining.saturating_add(increment),
+            TimeControl::PerMove(time) => time,
+        };
+        self.set_remaining(player, next);
+    }
+
+    /// The player whose clock ran out, if it has.
+    pub fn out_of_time(&self) -> Option<Player> {
+        let (player, _) = self.running?;
+        self.remaining(player).is_zero().then_some(player)
+    }
+
+    /// How long `player` should spend on their next move, leaving enough time
+    /// for the rest of the game.
+    pub fn time_for_move(&self, player: Player) -> Duration {
+        let remaining = self.remaining(player);
+        let time = match self.control {
+            // Plan for about 20 more moves
+            TimeControl::Increment { increment, .. } => (remaining / 20).saturating_add(increment),
+            TimeControl::PerMove(_) => remaining,
+        };
+        // Leave some time to actually make the move
+        time.min(remaining.mul_f64(0.9))
+    }
+
+    /// Stops the running clock without changing the time for the next move.
+    fn stop_running(&mut self) {
+        if let Some((player, _)) = self.running {
+            let remaining = self.remaining(player);
+            self.running = None;
+            self.set_remaining(player, remaining);
+        }
+    }
+}
+
+/// Shows each player's time on a line of its own, like `X 4:59.3`. The
+/// alternate form (`{:#}`) is plain text, without any colors.
+impl Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (player, _)) in self.remaining.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            // Rounded before it's split up, so 59.96 seconds is 1:00.0
+            let tenths = (self.remaining(*player).as_secs_f64() * 10.0).round() as u64;
+            let time = format!("{}:{:02}.{}", tenths / 600, tenths % 600 / 10, tenths % 10);
+            if f.alternate() {
+                write!(f, "{} {time}", player.symbol())?;
+            } else {
+                write!(f, "{} {time}", player.with_color())?;
+            }
+        }
+        Ok(())
+    }
+}
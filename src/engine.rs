@@ -0,0 +1,204 @@
+//! Talking to external engines (programs that choose moves), in a protocol
+//! like UCI's.
+//!
+//! Each message is a line on the engine's standard input or output.
+//! Coordinates are in the order players enter them in (see
+//! [`Game::map_player_coords`]).
+//!
+//! To the engine:
+//!
+//! - `newgame <dim> <width> <players>`: a new game is starting.
+//! - `position moves <move> ...`: every move played so far, oldest first, with
+//!   the coordinates of each separated by commas (e.g. `position moves 0,0
+//!   1,2`). Players take turns in order, so the engine can work out whose turn
+//!   it is.
+//! - `go movetime <milliseconds>`: asks for a move, to be made within the given
+//!   time.
+//! - `quit`: the engine should exit.
+//!
+//! From the engine:
+//!
+//! - `bestmove <x> <y> ...`: the engine's move, after a `go`.
+//!
+//! Any other lines from the engine (like progress reports) are ignored.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+
+use super::{board::Idx, Game};
+
+pub struct Engine {
+    /// The lines the engine sends, read on a thread of their own so that
+    /// waiting for them can time out
+    lines: Receiver<io::Result<String>>,
+    writer: Box<dyn Write + Send>,
+    child: Option<Child>,
+    movetime: Duration,
+    /// The most time the engine gets for its next move, if it's on a clock
+    pub(crate) time_limit: Option<Duration>,
+}
+
+/// How long engines get to choose a move when they're used as a
+/// [`Strategy`](crate::strategy::Strategy), unless it's changed with
+/// [`Engine::set_movetime`].
+pub const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+
+/// How much longer than its movetime an engine gets to reply, and how long
+/// it gets to exit after `quit`, before it's given up on.
+const GRACE: Duration = Duration::from_secs(1);
+
+impl Engine {
+    /// Starts an engine with `command`, which is split into the program and
+    /// its arguments at whitespace.
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Empty engine command"))?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let writer = child.stdin.take().unwrap();
+        let reader = child.stdout.take().unwrap();
+
+        let mut engine = Self::from_streams(reader, writer);
+        engine.child = Some(child);
+        Ok(engine)
+    }
+
+    /// Connects to an engine on another machine, which speaks the protocol
+    /// over TCP.
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        Ok(Self::from_streams(stream.try_clone()?, stream))
+    }
+
+    /// An engine that reads from `reader` and writes to `writer`, instead of
+    /// one running as a separate program.
+    pub fn from_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let failed = line.is_err();
+                if sender.send(line).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            lines,
+            writer: Box::new(writer),
+            child: None,
+            movetime: DEFAULT_MOVETIME,
+            time_limit: None,
+        }
+    }
+
+    pub fn movetime(&self) -> Duration {
+        self.movetime
+    }
+
+    pub fn set_movetime(&mut self, movetime: Duration) {
+        self.movetime = movetime;
+    }
+
+    /// Tells the engine a new game is starting.
+    pub fn new_game(&mut self, game: &Game) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "newgame {} {} {}",
+            game.dim(),
+            game.width(),
+            game.players().len()
+        )?;
+        self.writer.flush()
+    }
+
+    /// Asks the engine for a move in `game`, giving it `movetime` to think.
+    /// The move is returned in the order the board stores coordinates in,
+    /// like [`Game::hint`], but hasn't been checked to be legal.
+    ///
+    /// Engines that don't reply within a second of `movetime` are given up
+    /// on, with an error of kind [`TimedOut`](io::ErrorKind::TimedOut).
+    pub fn best_move(&mut self, game: &Game, movetime: Duration) -> io::Result<Idx> {
+        // Skip anything left over from a move the engine was too slow with
+        while self.lines.try_recv().is_ok() {}
+
+        let moves = game
+            .moves()
+            .map(|(coords, _)| game.map_player_coords(coords).iter().join(","))
+            .join(" ");
+        writeln!(self.writer, "position moves {moves}")?;
+        writeln!(self.writer, "go movetime {}", movetime.as_millis())?;
+        self.writer.flush()?;
+
+        let deadline = Instant::now() + movetime + GRACE;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(timeout) {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "The engine took too long to move",
+                    ))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "The engine exited",
+                    ))
+                }
+            };
+
+            let Some(coords) = line.trim().strip_prefix("bestmove ") else {
+                continue;
+            };
+            let coords: Idx = coords
+                .split_whitespace()
+                .map(|x| x.parse())
+                .collect::<Result<_, _>>()
+                .ok()
+                .filter(|coords: &Idx| coords.len() == game.dim())
+                .ok_or_else(|| {
+                    let message = format!("Invalid move from the engine: `{}`", line.trim());
+                    io::Error::new(io::ErrorKind::InvalidData, message)
+                })?;
+            return Ok(game.map_player_coords(coords));
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = writeln!(self.writer, "quit");
+        let _ = self.writer.flush();
+        // Engines that don't exit when they're asked to are killed
+        if let Some(child) = &mut self.child {
+            let deadline = Instant::now() + GRACE;
+            while let Ok(None) = child.try_wait() {
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+}
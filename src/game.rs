@@ -5,17 +5,30 @@ use std::{
 
 use itertools::Itertools;
 
-use crate::board;
+use crate::board::{self, Labels};
 
-use super::{Board, Piece, PlacePieceError, Player};
+use super::{Board, Highlight, Layout, Piece, PlacePieceError, Player};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Game {
-    pub board: Board<Piece>,
+    pub(crate) board: Board<Piece>,
     dim: usize,
     width: usize,
     players: Vec<Player>,
     last_piece: HashMap<Player, (usize, Piece)>,
+    history: Vec<Move>,
+    /// The Zobrist hash of the pieces on the board
+    hash: u64,
+}
+
+/// A move in a [`Game`]'s history, by index into the flattened board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Move {
+    index: usize,
+    piece: Piece,
+    /// The player's most recent piece before this one, to go back to when
+    /// the move is taken back
+    previous: Option<(usize, Piece)>,
 }
 
 impl Game {
@@ -29,9 +42,23 @@ impl Game {
                 .map(|n| n.try_into().expect("Valid number of players"))
                 .collect(),
             last_piece: HashMap::with_capacity(players as usize),
+            history: Vec::new(),
+            hash: 0,
         }
     }
 
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
     /// # Panics
     ///
     /// - Panics if `piece.player` is `None`.
@@ -45,15 +72,170 @@ impl Game {
             None => return Err(PlacePieceError::OutOfBounds),
         }
 
-        self.last_piece.insert(
-            piece.player.unwrap(),
-            (self.get_index(coords.clone().into()), piece.clone()),
-        );
+        let index = self.get_index(&coords);
+        let player = piece.player.unwrap();
+        let previous = self.last_piece.insert(player, (index, piece.clone()));
+        self.history.push(Move {
+            index,
+            piece: piece.clone(),
+            previous,
+        });
+        self.hash ^= zobrist_key(index, player);
         self.board[coords] = piece.into();
         Ok(())
     }
 
+    /// Places the current player's piece at `coords`. Along with
+    /// [`unmake_move`](Self::unmake_move), this only touches the cell being
+    /// played, so it's cheap enough to try moves out while searching.
+    pub fn make_move(&mut self, coords: board::Idx) -> Result<(), PlacePieceError> {
+        self.place_piece(Piece::new(self.current_player()), coords)
+    }
+
+    /// Takes back the most recent move, leaving the game exactly how it was
+    /// before the move. Returns where it was played and by whom, or `None` if
+    /// no moves have been played.
+    pub fn unmake_move(&mut self) -> Option<(board::Idx, Player)> {
+        let Move {
+            index,
+            piece,
+            previous,
+        } = self.history.pop()?;
+        let player = piece.player.unwrap();
+        let coords = self.get_coords(index);
+
+        self.board[coords.clone()] = Piece::empty().into();
+        self.hash ^= zobrist_key(index, player);
+
+        // The player's previous piece (if any) is now their most recent one.
+        match previous {
+            Some(previous) => self.last_piece.insert(player, previous),
+            None => self.last_piece.remove(&player),
+        };
+
+        Some((coords, player))
+    }
+
+    /// Takes back the most recent move, like
+    /// [`unmake_move`](Self::unmake_move).
+    pub fn undo(&mut self) -> Option<(board::Idx, Player)> {
+        self.unmake_move()
+    }
+
+    /// A hash of the pieces on the board, which is the same however they got
+    /// there. It's kept up to date as moves are made, so it's cheap to get.
+    pub fn position_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The most recent move, if any.
+    pub fn last_move(&self) -> Option<(board::Idx, Player)> {
+        self.history
+            .last()
+            .map(|m| (self.get_coords(m.index), m.piece.player.unwrap()))
+    }
+
+    /// All the moves played so far, oldest first.
+    pub fn moves(&self) -> impl Iterator<Item = (board::Idx, Player)> + '_ {
+        self.history
+            .iter()
+            .map(|m| (self.get_coords(m.index), m.piece.player.unwrap()))
+    }
+
+    /// Suggests a move for `player`: one that wins on the spot if there is
+    /// one, otherwise one that blocks an opponent's immediate win, otherwise
+    /// the first empty cell. Returns `None` if the board is full.
+    pub fn hint(&self, player: Player) -> Option<board::Idx> {
+        let empty: Vec<_> = self.legal_moves().collect();
+        self.urgent_move(player, &empty)
+            .or_else(|| empty.into_iter().next())
+    }
+
+    /// The coordinates of every empty cell, in the order the board stores
+    /// them in (use [`map_player_coords`](Self::map_player_coords) to show
+    /// them to players).
+    pub fn legal_moves(&self) -> impl Iterator<Item = board::Idx> + '_ {
+        (0..self.width.pow(self.dim as u32))
+            .map(|i| self.get_coords(i))
+            .filter(|coords| self.is_legal(coords))
+    }
+
+    /// Whether a piece can be placed at `coords` (in the order the board
+    /// stores them in), which is when [`place_piece`](Self::place_piece)
+    /// would succeed.
+    pub fn is_legal(&self, coords: &board::Idx) -> bool {
+        matches!(self.piece(coords), Some(Piece { player: None }))
+    }
+
+    /// The piece at `coords` (in the order the board stores them in), or
+    /// `None` if they're off the board.
+    pub fn piece(&self, coords: &board::Idx) -> Option<&Piece> {
+        // Walk down the board without copying the coordinates
+        let mut board = &self.board;
+        for x in coords {
+            match board {
+                Board::Nd(boards) => board = boards.get(*x)?,
+                Board::Piece(_) => return None,
+            }
+        }
+        match board {
+            Board::Piece(piece) => Some(piece),
+            Board::Nd(_) => None,
+        }
+    }
+
+    /// One of the `empty` cells that wins on the spot for `player`, otherwise
+    /// one that blocks an opponent's immediate win.
+    pub(crate) fn urgent_move(&self, player: Player, empty: &[board::Idx]) -> Option<board::Idx> {
+        // Try each move out on a single copy of the game
+        let mut game = self.clone();
+        let mut wins_at = |player: Player, coords: &board::Idx| {
+            if game
+                .place_piece(Piece::new(player), coords.clone())
+                .is_err()
+            {
+                return false;
+            }
+            let wins = game.check_win(player);
+            game.unmake_move();
+            wins
+        };
+
+        if let Some(coords) = empty.iter().find(|c| wins_at(player, c)) {
+            return Some(coords.clone());
+        }
+
+        self.players
+            .iter()
+            .filter(|p| **p != player)
+            .find_map(|opponent| empty.iter().find(|c| wins_at(*opponent, c)))
+            .cloned()
+    }
+
+    /// How the game ended: the player who just moved won, or the board is
+    /// full. Returns `None` if the game isn't over yet.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        let (_, player) = self.last_move()?;
+        if self.check_win(player) {
+            Some(GameOutcome::Win(player))
+        } else if self.is_full() {
+            Some(GameOutcome::Draw)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.history.len() == self.width.pow(self.dim as u32)
+    }
+
     pub fn check_win(&self, player: Player) -> bool {
+        self.winning_line(player).is_some()
+    }
+
+    /// The cells of a line completed by `player`'s most recent piece, ordered
+    /// from one end of the line to the other.
+    pub fn winning_line(&self, player: Player) -> Option<Vec<board::Idx>> {
         // Check this piece with all the other pieces
         // For there to be a win, each dimension must satisfy one of the following:
         // 1. All the pieces are the same
@@ -64,7 +246,7 @@ impl Game {
 
         let piece = match &self.last_piece.get(&player) {
             Some((i, piece)) => (*i, piece),
-            None => return false,
+            None => return None,
         };
 
         let pieces = board
@@ -93,18 +275,23 @@ impl Game {
 
                 // Check if all the pieces are either all the same or all different
                 let comp_set: HashSet<usize, RandomState> = HashSet::from_iter(coords.clone());
-                return (comp_set.len() == 1 || comp_set.len() == self.width)
+                (comp_set.len() == 1 || comp_set.len() == self.width)
                     && Self::combination_no_wraparound(
                         combination.iter().map(|(i, _)| *i).collect(),
-                    );
+                    )
             });
 
             if combination_works {
-                return true;
+                return Some(
+                    combination
+                        .iter()
+                        .map(|(i, _)| self.get_coords(*i))
+                        .collect(),
+                );
             }
         }
 
-        false
+        None
     }
 
     fn combination_no_wraparound(coords: Vec<usize>) -> bool {
@@ -139,7 +326,14 @@ impl Game {
             / self.width.pow((dim - 1) as u32)
     }
 
-    fn get_index(&self, coords: Vec<usize>) -> usize {
+    pub(crate) fn get_coords(&self, index: usize) -> board::Idx {
+        (1..=self.dim)
+            .rev()
+            .map(|dim| self.get_coord(index, dim))
+            .collect()
+    }
+
+    fn get_index(&self, coords: &board::Idx) -> usize {
         coords
             .iter()
             .rev()
@@ -149,28 +343,175 @@ impl Game {
     }
 
     pub fn current_player(&self) -> Player {
-        // Get number of pieces on the board
-        let num_pieces = self
-            .board
-            .flatten()
-            .iter()
-            .filter(|e| e.player.is_some())
-            .count();
-
-        // Get the player whose turn it is
-        self.players[num_pieces % self.players.len()]
+        self.players[self.history.len() % self.players.len()]
     }
 }
 
+/// The number XORed into a [`Game`]'s hash for a piece of `player`'s at
+/// `index`. These are made up on the spot rather than kept in a table, by
+/// mixing the bits of the index and player (with SplitMix64).
+fn zobrist_key(index: usize, player: Player) -> u64 {
+    let mut x = ((index as u64) << 21 | player.symbol() as u64).wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// The end of a [`Game`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    Win(Player),
+    Draw,
+}
+
+/// How to display a [`Game`].
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    /// Whether or not to pad each piece with spaces
+    pub hide_padding: bool,
+    /// Whether or not to label the axes with coordinates
+    pub labels: bool,
+    /// Cells to draw in a different style
+    pub highlights: HashMap<board::Idx, Highlight>,
+    /// Whether or not to stick to plain ASCII, without colors or other escape
+    /// codes
+    pub plain: bool,
+    /// Whether or not to leave out the empty 2D boards, showing a map of the
+    /// boards followed by the ones with pieces on them instead. Has no effect
+    /// on boards with less than 3 dimensions.
+    pub compact: bool,
+}
+
 impl Game {
     pub fn display(&self, hide_padding: bool) -> String {
         self.board
             .display(board::Direction::Horizontal, hide_padding)
     }
+
+    pub fn display_with(&self, options: &DisplayOptions) -> String {
+        if options.compact && self.dim > 2 {
+            return self.display_compact(options);
+        }
+
+        display_board(
+            &self.board,
+            board::Direction::Horizontal,
+            &self.axis_names(),
+            options,
+        )
+    }
+
+    /// Highlights the most recent move, and the line it completed if it won
+    /// the game.
+    pub fn highlights(&self) -> HashMap<board::Idx, Highlight> {
+        let Some((coords, player)) = self.last_move() else {
+            return HashMap::new();
+        };
+
+        let mut highlights = HashMap::from([(coords, Highlight::LastMove)]);
+        for coords in self.winning_line(player).unwrap_or_default() {
+            highlights.insert(coords, Highlight::WinningLine);
+        }
+        highlights
+    }
+
+    /// Where each piece is in the output of [`Game::display_with`] (which
+    /// isn't affected by `options.compact`).
+    pub fn layout(&self, options: &DisplayOptions) -> Layout {
+        let layout = self
+            .board
+            .layout(board::Direction::Horizontal, options.hide_padding);
+
+        if options.labels {
+            self.labels(options.hide_padding).offset(layout)
+        } else {
+            layout
+        }
+    }
+
+    pub(crate) fn labels(&self, hide_padding: bool) -> Labels {
+        self.board
+            .layout(board::Direction::Horizontal, hide_padding)
+            .labels(&self.axis_names(), board::Direction::Horizontal)
+    }
+
+    /// The name of each axis, in the order the board stores them. The names
+    /// are letters in the order players enter coordinates in.
+    pub(crate) fn axis_names(&self) -> Vec<String> {
+        self.map_player_order((0..self.dim).collect())
+            .into_iter()
+            .map(board::axis_name)
+            .collect()
+    }
+
+    /// Converts between the order players enter coordinates in and the order
+    /// the board stores them in. (The mapping is its own inverse.)
+    ///
+    /// Players enter an `x y` pair for each 2D board from the outside in, with
+    /// a lone `x` first if there are an odd number of dimensions.
+    pub fn map_player_coords(&self, coords: board::Idx) -> board::Idx {
+        self.map_player_order(coords.into()).into()
+    }
+
+    /// [`Game::map_player_coords`] for anything with one entry per axis.
+    pub(crate) fn map_player_order<T>(&self, axes: Vec<T>) -> Vec<T> {
+        if self.dim.is_multiple_of(2) {
+            return axes;
+        }
+
+        let chunks = axes
+            .into_iter()
+            // Turns `x1 y2 x2` into `x2 y2 x1`
+            .rev()
+            // Then `(x2 y2) (x1)`
+            .chunks(2);
+
+        let mut chunks_vec = Vec::new();
+        for chunk in &chunks {
+            chunks_vec.push(chunk);
+        }
+
+        chunks_vec
+            .into_iter()
+            // Then `(x1) (x2 y2)`
+            .rev()
+            // Then `x1 x2 y2`
+            .flatten()
+            .collect()
+    }
+}
+
+/// Displays a board (or part of one) starting in `direction`, with the axes
+/// named `names`.
+pub(crate) fn display_board<T: Display + std::fmt::Debug>(
+    board: &Board<T>,
+    direction: board::Direction,
+    names: &[String],
+    options: &DisplayOptions,
+) -> String {
+    let display = board.display_with(
+        direction,
+        options.hide_padding,
+        &options.highlights,
+        options.plain,
+    );
+
+    if options.labels {
+        board
+            .layout(direction, options.hide_padding)
+            .labels(names, direction)
+            .apply(&display)
+    } else {
+        display
+    }
 }
 
 impl Display for Game {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!("{}", self.board))
+        if f.alternate() {
+            write!(f, "{:#}", self.board)
+        } else {
+            write!(f, "{}", self.board)
+        }
     }
 }
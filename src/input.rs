@@ -0,0 +1,277 @@
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
+
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, history::DefaultHistory,
+    validate::Validator, Context, Editor, Helper,
+};
+
+/// The names of the in-game commands, used for tab completion.
+const COMMANDS: [&str; 6] = ["undo", "save", "hint", "view", "quit", "help"];
+
+pub const HELP: &str = "\
+Enter the coordinates of your move separated by spaces (e.g. `1 0 2`), or one of:
+  undo         Take back the last move
+  save [file]  Save the game (to `nd-tic-tac-toe.save` by default)
+  hint         Suggest a move
+  view [a=1 ...|occupied]
+               Only show the part of the board with the given coordinates
+               (named by letter like the labels, or `x1`, `x2`, ...), only the
+               2D boards with pieces on them, or (with no arguments) everything
+  quit         Quit the game
+  help         Show this message";
+
+pub const ULTIMATE_HELP: &str = "\
+Enter the coordinates of the small board and then of the cell on it, separated
+by spaces (e.g. `1 1 0 2`), or one of:
+  undo         Take back the last move
+  quit         Quit the game
+  help         Show this message
+
+Winning a small board claims it on the big board. Your move sends the next
+player to the small board in the same place as the cell you played (shown in
+green, or with `+`), unless it's already won or full.";
+
+pub const DEFAULT_SAVE_PATH: &str = "nd-tic-tac-toe.save";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Move(VecDeque<usize>),
+    Undo,
+    Save(Option<String>),
+    Hint,
+    View(View),
+    Quit,
+    Help,
+}
+
+/// Which part of the board to show.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum View {
+    #[default]
+    Full,
+    /// Fixes the axes (numbered in the order coordinates are entered in, from
+    /// 0) to the given values.
+    Slice(Vec<(usize, usize)>),
+    /// Only the 2D boards with pieces on them.
+    Occupied,
+}
+
+impl View {
+    /// The coordinates to pass to [`nd_tic_tac_toe::Game::display_slice`].
+    pub fn fixed(axes: &[(usize, usize)], dim: usize) -> Option<Vec<Option<usize>>> {
+        let mut fixed = vec![None; dim];
+        for (axis, value) in axes {
+            *fixed.get_mut(*axis)? = Some(*value);
+        }
+        Some(fixed)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseCommandError {
+    Empty,
+    UnknownCommand(String),
+    InvalidCoordinate(String),
+    InvalidAxis(String),
+}
+
+impl Display for ParseCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCommandError::Empty => f.write_str("Invalid input"),
+            ParseCommandError::UnknownCommand(command) => {
+                write!(f, "Unknown command `{command}` (type `help` for a list)")
+            }
+            ParseCommandError::InvalidCoordinate(coord) => {
+                write!(f, "Invalid coordinate `{coord}`")
+            }
+            ParseCommandError::InvalidAxis(axis) => {
+                write!(f, "Invalid axis `{axis}` (try `a=1` or `x1=1`)")
+            }
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = ParseCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let Some(first) = words.next() else {
+            return Err(ParseCommandError::Empty);
+        };
+
+        // Anything starting with a number is a move
+        if first.starts_with(|c: char| c.is_ascii_digit()) {
+            return s
+                .split_whitespace()
+                .map(|x| {
+                    x.parse()
+                        .map_err(|_| ParseCommandError::InvalidCoordinate(x.to_string()))
+                })
+                .collect::<Result<_, _>>()
+                .map(Command::Move);
+        }
+
+        match first.to_lowercase().as_str() {
+            "undo" => Ok(Command::Undo),
+            "save" => Ok(Command::Save(words.next().map(|s| s.to_string()))),
+            "hint" => Ok(Command::Hint),
+            "view" => parse_view(words).map(Command::View),
+            "quit" | "exit" => Ok(Command::Quit),
+            "help" | "?" => Ok(Command::Help),
+            _ => Err(ParseCommandError::UnknownCommand(first.to_string())),
+        }
+    }
+}
+
+fn parse_view<'a>(words: impl Iterator<Item = &'a str>) -> Result<View, ParseCommandError> {
+    let words: Vec<_> = words.collect();
+    match words.as_slice() {
+        [] => return Ok(View::Full),
+        ["occupied" | "pieces"] => return Ok(View::Occupied),
+        _ => (),
+    }
+
+    words
+        .into_iter()
+        .map(|word| {
+            let invalid_axis = || ParseCommandError::InvalidAxis(word.to_string());
+            let (axis, value) = word.split_once('=').ok_or_else(invalid_axis)?;
+
+            // Either a letter like the labels, or `x` and a number starting from 1
+            let axis = match axis.strip_prefix('x') {
+                Some(n) if !n.is_empty() => n
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .ok_or_else(invalid_axis)?,
+                _ => match axis.as_bytes() {
+                    [c @ b'a'..=b'z'] => (c - b'a') as usize,
+                    _ => return Err(invalid_axis()),
+                },
+            };
+            let value = value
+                .parse()
+                .map_err(|_| ParseCommandError::InvalidCoordinate(value.to_string()))?;
+
+            Ok((axis, value))
+        })
+        .collect::<Result<_, _>>()
+        .map(View::Slice)
+}
+
+/// Reads lines from the terminal with line editing, history, and tab
+/// completion of commands.
+pub struct Input {
+    editor: Editor<CommandHelper, DefaultHistory>,
+}
+
+impl Input {
+    pub fn new() -> rustyline::Result<Self> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(CommandHelper));
+        Ok(Self { editor })
+    }
+
+    /// Reads a line, adding it to the history if it isn't blank.
+    pub fn read_line(&mut self) -> rustyline::Result<String> {
+        let line = self.editor.readline("> ")?;
+        if !line.trim().is_empty() {
+            self.editor.add_history_entry(line.as_str())?;
+        }
+        Ok(line)
+    }
+}
+
+struct CommandHelper;
+
+impl Completer for CommandHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Only the command itself (the first word) is completed
+        let line = &line[..pos];
+        if line.contains(char::is_whitespace) {
+            return Ok((pos, vec![]));
+        }
+
+        let candidates = COMMANDS
+            .iter()
+            .filter(|command| command.starts_with(line))
+            .map(|command| command.to_string())
+            .collect();
+        Ok((0, candidates))
+    }
+}
+
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(
+            "1 0  2".parse::<Command>(),
+            Ok(Command::Move(vec![1, 0, 2].into()))
+        );
+        assert_eq!(
+            "1 x 2".parse::<Command>(),
+            Err(ParseCommandError::InvalidCoordinate("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("undo".parse::<Command>(), Ok(Command::Undo));
+        assert_eq!(" Hint ".parse::<Command>(), Ok(Command::Hint));
+        assert_eq!("save".parse::<Command>(), Ok(Command::Save(None)));
+        assert_eq!(
+            "save game.txt".parse::<Command>(),
+            Ok(Command::Save(Some("game.txt".to_string())))
+        );
+        assert_eq!(
+            "frobnicate".parse::<Command>(),
+            Err(ParseCommandError::UnknownCommand("frobnicate".to_string()))
+        );
+        assert_eq!("".parse::<Command>(), Err(ParseCommandError::Empty));
+    }
+
+    #[test]
+    fn test_parse_view() {
+        assert_eq!("view".parse::<Command>(), Ok(Command::View(View::Full)));
+        assert_eq!(
+            "view occupied".parse::<Command>(),
+            Ok(Command::View(View::Occupied))
+        );
+        assert_eq!(
+            "view x3=2 d=0".parse::<Command>(),
+            Ok(Command::View(View::Slice(vec![(2, 2), (3, 0)])))
+        );
+        assert_eq!(
+            "view x0=2".parse::<Command>(),
+            Err(ParseCommandError::InvalidAxis("x0=2".to_string()))
+        );
+        assert_eq!(
+            "view c".parse::<Command>(),
+            Err(ParseCommandError::InvalidAxis("c".to_string()))
+        );
+        assert_eq!(View::fixed(&[(2, 2)], 3), Some(vec![None, None, Some(2)]));
+        assert_eq!(View::fixed(&[(3, 2)], 3), None);
+    }
+}
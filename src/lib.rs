@@ -1,14 +1,34 @@
 mod board;
+pub mod clock;
+pub mod engine;
 mod game;
+mod lobby;
+mod matches;
+mod notation;
 mod piece;
 mod player;
+pub mod protocol;
+pub mod ratings;
+pub mod record;
+mod server;
+mod slice;
+pub mod strategy;
+mod svg;
+pub mod tournament;
+pub mod ultimate;
+#[cfg(feature = "web")]
+pub mod web;
 
 use std::fmt::Display;
 
 use board::Board;
-pub use game::Game;
+pub use board::{Highlight, Layout};
+pub use game::{DisplayOptions, Game, GameOutcome};
+pub use lobby::{GameInfo, DEFAULT_IDLE_TIMEOUT, MAX_DIM};
+pub use matches::{play_game, play_match, MatchResult};
 pub use piece::Piece;
-use player::Player;
+pub use player::Player;
+pub use server::Server;
 
 #[derive(Debug)]
 pub enum PlacePieceError {
@@ -25,10 +45,77 @@ impl Display for PlacePieceError {
     }
 }
 
+/// Why a position couldn't be read with [`Game::from_notation`].
+#[derive(Debug, PartialEq)]
+pub enum NotationError {
+    /// A field was missing or couldn't be read.
+    Malformed,
+    /// The width wasn't one more than the number of dimensions, which is the
+    /// only width games can have.
+    UnsupportedWidth,
+    /// There were more than [`MAX_DIM`] dimensions.
+    TooManyDimensions,
+    /// There weren't as many cells as there are on the board.
+    WrongCellCount,
+    /// The number of pieces each player has doesn't fit with whose turn it is.
+    ImpossibleTurn,
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotationError::Malformed => f.write_str("Malformed notation"),
+            NotationError::UnsupportedWidth => {
+                f.write_str("The width must be one more than the number of dimensions")
+            }
+            NotationError::TooManyDimensions => {
+                write!(f, "Games can have at most {MAX_DIM} dimensions")
+            }
+            NotationError::WrongCellCount => f.write_str("Wrong number of cells"),
+            NotationError::ImpossibleTurn => {
+                f.write_str("The number of pieces doesn't fit with whose turn it is")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SliceError {
+    /// There wasn't exactly one (possibly free) coordinate for each axis.
+    WrongDimension,
+    OutOfBounds,
+}
+
+impl Display for SliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceError::WrongDimension => f.write_str("Wrong number of coordinates"),
+            SliceError::OutOfBounds => f.write_str("Out of bounds"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::{HashMap, VecDeque};
+
+    use itertools::Itertools;
+
     use super::*;
 
+    fn strip_escape_codes(s: &str) -> String {
+        let mut chars = s.chars();
+        let mut stripped = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                stripped.push(c);
+            }
+        }
+        stripped
+    }
+
     fn setup_3d_winner(winner: Player, loser: Player) -> Game {
         let mut game = Game::new(3, 2);
         let p0 = loser;
@@ -212,6 +299,1298 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_2d_labels() {
+        let board = Board::<char>::new(vec![3, 3], '.');
+        let display = board.display(board::Direction::Horizontal, false);
+        let labels = board.layout(board::Direction::Horizontal, false).labels(
+            &["a".to_string(), "b".to_string()],
+            board::Direction::Horizontal,
+        );
+
+        assert_eq!(
+            labels.apply(&display),
+            "b  0 1 2  a\n      \n0  . . . \n1  . . . \n2  . . . "
+        );
+
+        // Labels with more than one digit, and names past `z`
+        let board = Board::<char>::new(vec![11], '.');
+        let display = board.display(board::Direction::Horizontal, true);
+        let labels = board
+            .layout(board::Direction::Horizontal, true)
+            .labels(&[board::axis_name(26)], board::Direction::Horizontal);
+        assert_eq!(labels.apply(&display), " 012345678910 x27\n ........... ");
+        assert_eq!(board::axis_name(25), "z");
+    }
+
+    #[test]
+    fn test_labels_layout() {
+        let mut game = Game::new(3, 2);
+        game.place_piece(Piece::new(Player::new('X')), vec![2, 1, 3].into())
+            .unwrap();
+        let options = DisplayOptions {
+            labels: true,
+            ..Default::default()
+        };
+
+        let display = game.display_with(&options);
+        let (line, column) = game
+            .layout(&options)
+            .position(&vec![2, 1, 3].into())
+            .unwrap();
+        let line: Vec<char> = strip_escape_codes(display.lines().nth(line).unwrap())
+            .chars()
+            .collect();
+
+        assert_eq!(line[column], 'X');
+    }
+
+    #[test]
+    fn test_plain_display() {
+        let mut game = Game::new(2, 2);
+        game.place_piece(Piece::new(Player::new('X')), vec![0, 0].into())
+            .unwrap();
+        game.place_piece(Piece::new(Player::new('O')), vec![1, 2].into())
+            .unwrap();
+
+        let display = game.display_with(&DisplayOptions {
+            plain: true,
+            highlights: game.highlights(),
+            ..Default::default()
+        });
+        assert_eq!(display, "    \n X . . \n . . . \n . O . ");
+        assert_eq!(format!("{game:#}"), display);
+        assert!(display.is_ascii());
+    }
+
+    #[test]
+    fn test_svg() {
+        let p0 = Player::new('X');
+        let p1 = Player::new('O');
+        let game = setup_3d_winner(p0, p1);
+
+        let svg = game.to_svg(&DisplayOptions {
+            labels: true,
+            highlights: game.highlights(),
+            ..Default::default()
+        });
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+
+        // One shaded board for each 2D slice
+        assert_eq!(svg.matches(r#"rx="6""#).count(), 4);
+        // All the pieces, and the empty cells
+        assert_eq!(svg.matches(">X</text>").count(), 6);
+        assert_eq!(svg.matches(">O</text>").count(), 6);
+        assert_eq!(svg.matches(r#"r="3""#).count(), 64 - 12);
+        // The winning line
+        assert_eq!(svg.matches(r#"fill-opacity="0.25""#).count(), 4);
+    }
+
+    #[test]
+    fn test_replay_svg() {
+        let p0 = Player::new('X');
+        let p1 = Player::new('O');
+        let game = setup_3d_winner(p0, p1);
+        let options = DisplayOptions::default();
+
+        let frames = game.replay_svg_frames(&options);
+        assert_eq!(frames.len(), 12);
+        // Every frame highlights its move, and only the last one has a winner
+        for frame in &frames[..11] {
+            assert_eq!(frame.matches("stroke-dasharray").count(), 1);
+            assert!(!frame.contains("fill-opacity"));
+        }
+        assert_eq!(frames[11].matches("fill-opacity").count(), 4);
+        assert_eq!(
+            frames[11],
+            game.to_svg(&DisplayOptions {
+                highlights: game.highlights(),
+                ..Default::default()
+            })
+        );
+
+        let animated = game.replay_svg(&options, 0.5);
+        assert_eq!(animated.matches("<animate ").count(), 12);
+        assert!(animated.contains(r#"dur="6s""#));
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut game = Game::new(3, 2);
+        game.place_piece(Piece::new(Player::new('X')), vec![2, 1, 3].into())
+            .unwrap();
+        game.place_piece(Piece::new(Player::new('O')), vec![2, 0, 0].into())
+            .unwrap();
+        let options = DisplayOptions {
+            plain: true,
+            ..Default::default()
+        };
+
+        // Players enter `2 3 1` for the `X`
+        assert_eq!(
+            game.display_slice(&[Some(2), None, None], &options),
+            Ok("\n O . . . \n . . . X \n . . . . \n . . . . \n".to_string())
+        );
+        assert_eq!(
+            game.display_slice(&[None, Some(3), Some(1)], &options),
+            Ok(" . . X . ".to_string())
+        );
+        assert_eq!(
+            game.display_slice(&[None, Some(4), None], &options),
+            Err(SliceError::OutOfBounds)
+        );
+        assert_eq!(
+            game.display_slice(&[None, None], &options),
+            Err(SliceError::WrongDimension)
+        );
+    }
+
+    #[test]
+    fn test_occupied_slices() {
+        let mut game = Game::new(3, 2);
+        let options = DisplayOptions {
+            plain: true,
+            ..Default::default()
+        };
+        assert_eq!(game.display_occupied_slices(&options), "The board is empty");
+
+        game.place_piece(Piece::new(Player::new('X')), vec![2, 1, 3].into())
+            .unwrap();
+        game.place_piece(Piece::new(Player::new('O')), vec![0, 0, 0].into())
+            .unwrap();
+        assert_eq!(
+            game.display_occupied_slices(&options),
+            format!(
+                "a=0\n{}\n\na=2\n{}",
+                game.display_slice(&[Some(0), None, None], &options)
+                    .unwrap(),
+                game.display_slice(&[Some(2), None, None], &options)
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_compact_display() {
+        let mut game = Game::new(3, 2);
+        game.place_piece(Piece::new(Player::new('X')), vec![2, 1, 3].into())
+            .unwrap();
+        let options = DisplayOptions {
+            plain: true,
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            game.display_with(&options),
+            format!(" . . # . \n\n{}", game.display_occupied_slices(&options))
+        );
+
+        // Boards with less than 3 dimensions are always shown in full
+        let game = Game::new(2, 2);
+        assert_eq!(game.display_with(&options), format!("{game:#}"));
+    }
+
+    #[test]
+    fn test_protocol_messages() {
+        use protocol::{ClientMessage, ServerMessage};
+
+        assert_eq!(
+            "move 1 0 2".parse::<ClientMessage>(),
+            Ok(ClientMessage::Move(vec![1, 0, 2].into()))
+        );
+        assert!("move".parse::<ClientMessage>().is_err());
+        assert!("join 1 2".parse::<ClientMessage>().is_err());
+
+        let messages = [
+            ClientMessage::Create { dim: 3, players: 2 },
+            ClientMessage::List,
+            ClientMessage::Join(None),
+            ClientMessage::Join(Some(4)),
+            ClientMessage::Rejoin {
+                game: 4,
+                token: 0xdeadbeef,
+            },
+            ClientMessage::Spectate(4),
+        ];
+        for message in messages {
+            assert_eq!(message.to_string().parse(), Ok(message));
+        }
+
+        let messages = [
+            ServerMessage::Created(4),
+            ServerMessage::Games(vec![]),
+            ServerMessage::Games(vec![GameInfo {
+                id: 4,
+                dim: 3,
+                players: 2,
+                open_seats: 1,
+                spectators: 3,
+            }]),
+            ServerMessage::Join {
+                player: Player::new('O'),
+                dim: 3,
+                players: 2,
+                game: 4,
+                token: u64::MAX,
+            },
+            ServerMessage::Spectate {
+                game: 4,
+                dim: 3,
+                players: 2,
+            },
+            ServerMessage::State(vec![vec![0, 1, 2].into(), vec![3, 3, 3].into()]),
+            ServerMessage::State(vec![]),
+            ServerMessage::Error("Not your turn".to_string()),
+            ServerMessage::Result(GameOutcome::Win(Player::new('X'))),
+            ServerMessage::Result(GameOutcome::Draw),
+        ];
+        for message in messages {
+            assert_eq!(message.to_string().parse(), Ok(message));
+        }
+    }
+
+    /// A client of [`Server`], speaking the protocol by hand.
+    struct TestClient(std::io::BufReader<std::net::TcpStream>);
+
+    impl TestClient {
+        fn connect(address: std::net::SocketAddr) -> Self {
+            let stream = std::net::TcpStream::connect(address).unwrap();
+            stream
+                .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+                .unwrap();
+            Self(std::io::BufReader::new(stream))
+        }
+
+        fn send(&mut self, line: &str) {
+            use std::io::Write;
+            writeln!(self.0.get_mut(), "{line}").unwrap();
+        }
+
+        fn receive(&mut self) -> String {
+            use std::io::BufRead;
+            let mut line = String::new();
+            self.0.read_line(&mut line).unwrap();
+            line.trim_end().to_string()
+        }
+
+        /// Joins a game, returning the message saying so.
+        fn join(&mut self, line: &str) -> protocol::ServerMessage {
+            self.send(line);
+            let message = self.receive().parse().unwrap();
+            assert!(matches!(message, protocol::ServerMessage::Join { .. }));
+            message
+        }
+    }
+
+    fn start_server(
+        games: &[(usize, u32)],
+        idle_timeout: std::time::Duration,
+    ) -> std::net::SocketAddr {
+        let mut server = Server::bind("127.0.0.1:0", idle_timeout).unwrap();
+        for (dim, players) in games {
+            server.host(Game::new(*dim, *players));
+        }
+        let address = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+        address
+    }
+
+    #[test]
+    fn test_server() {
+        let address = start_server(&[(2, 2)], DEFAULT_IDLE_TIMEOUT);
+        let mut clients = [TestClient::connect(address), TestClient::connect(address)];
+
+        assert!(clients[0]
+            .join("join")
+            .to_string()
+            .starts_with("join X 2 2 1 "));
+        assert_eq!(clients[0].receive(), "state");
+        assert!(clients[1]
+            .join("join 1")
+            .to_string()
+            .starts_with("join O 2 2 1 "));
+        for client in &mut clients {
+            assert_eq!(client.receive(), "state");
+        }
+
+        clients[1].send("move 1 1");
+        assert_eq!(clients[1].receive(), "error Not your turn");
+        clients[0].send("move 0 0");
+        for client in &mut clients {
+            assert_eq!(client.receive(), "state 0,0");
+        }
+        clients[1].send("move 0 0");
+        assert_eq!(clients[1].receive(), "error Occupied");
+
+        let moves = [(1, "1 0"), (0, "0 1"), (1, "1 1"), (0, "0 2")];
+        for (seat, coords) in moves {
+            clients[seat].send(&format!("move {coords}"));
+            for client in &mut clients {
+                assert!(client.receive().starts_with("state"));
+            }
+        }
+        for client in &mut clients {
+            assert_eq!(client.receive(), "result X");
+        }
+    }
+
+    #[test]
+    fn test_lobby() {
+        let address = start_server(&[(2, 2)], DEFAULT_IDLE_TIMEOUT);
+        let mut host = TestClient::connect(address);
+        host.send("create 7 2");
+        assert_eq!(
+            host.receive(),
+            "error Games need 1 to 3 players and 1 to 6 dimensions"
+        );
+        host.send("create 3 2");
+        assert_eq!(host.receive(), "created 2");
+        host.join("join 2");
+        assert_eq!(host.receive(), "state");
+        host.send("join 1");
+        assert_eq!(host.receive(), "error Already in a game");
+
+        let mut other = TestClient::connect(address);
+        other.send("list");
+        assert_eq!(other.receive(), "games 1,2,2,2,0 2,3,2,1,0");
+        other.send("join 3");
+        assert_eq!(other.receive(), "error No such game");
+        other.send("move 0 0 0");
+        assert_eq!(other.receive(), "error Join a game first");
+    }
+
+    #[test]
+    fn test_server_rejoin() {
+        use protocol::ServerMessage;
+
+        let address = start_server(&[(2, 2)], DEFAULT_IDLE_TIMEOUT);
+        let mut x = TestClient::connect(address);
+        let mut o = TestClient::connect(address);
+        x.join("join");
+        x.receive();
+        let ServerMessage::Join { token, .. } = o.join("join") else {
+            unreachable!()
+        };
+        x.receive();
+        o.receive();
+        x.send("move 0 0");
+        x.receive();
+        o.receive();
+
+        // O drops out, and someone else can't take their seat
+        drop(o);
+        assert_eq!(
+            x.receive(),
+            "error Player O was disconnected (they can rejoin)"
+        );
+        let mut o = TestClient::connect(address);
+        o.send("join 1");
+        assert_eq!(o.receive(), "error The game is full");
+        o.send(&format!("rejoin 1 {:x}", token.wrapping_add(1)));
+        assert_eq!(o.receive(), "error Wrong token");
+
+        // Until they come back
+        o.join(&format!("rejoin 1 {token:x}"));
+        for client in [&mut x, &mut o] {
+            assert_eq!(client.receive(), "state 0,0");
+        }
+        o.send("move 1 1");
+        for client in [&mut x, &mut o] {
+            assert_eq!(client.receive(), "state 0,0 1,1");
+        }
+    }
+
+    #[test]
+    fn test_server_idle_timeout() {
+        let address = start_server(&[(2, 2)], std::time::Duration::from_millis(100));
+        let mut client = TestClient::connect(address);
+        client.join("join");
+        assert_eq!(client.receive(), "state");
+
+        assert_eq!(client.receive(), "error Game 1 was closed for being idle");
+        client.send("list");
+        assert_eq!(client.receive(), "games");
+    }
+
+    #[test]
+    fn test_spectator() {
+        let address = start_server(&[(2, 2)], DEFAULT_IDLE_TIMEOUT);
+        let mut x = TestClient::connect(address);
+        x.join("join");
+        x.receive();
+        x.send("move 0 0");
+        assert_eq!(x.receive(), "error Waiting for players");
+
+        // Spectators see the game so far when they start watching
+        let mut spectator = TestClient::connect(address);
+        spectator.send("spectate 1");
+        assert_eq!(spectator.receive(), "spectate 1 2 2");
+        assert_eq!(spectator.receive(), "state");
+
+        let mut o = TestClient::connect(address);
+        o.join("join");
+        for client in [&mut x, &mut o, &mut spectator] {
+            assert_eq!(client.receive(), "state");
+        }
+        spectator.send("list");
+        assert_eq!(spectator.receive(), "games 1,2,2,0,1");
+
+        // And can't play
+        spectator.send("move 0 0");
+        assert_eq!(spectator.receive(), "error Spectators can't place pieces");
+        spectator.send("join 1");
+        assert_eq!(spectator.receive(), "error Already in a game");
+
+        let moves = [(0, "0 0"), (1, "1 0"), (0, "0 1"), (1, "1 1"), (0, "0 2")];
+        for (seat, coords) in moves {
+            [&mut x, &mut o][seat].send(&format!("move {coords}"));
+            for client in [&mut x, &mut o, &mut spectator] {
+                assert!(client.receive().starts_with("state"));
+            }
+        }
+        for client in [&mut x, &mut o, &mut spectator] {
+            assert_eq!(client.receive(), "result X");
+        }
+
+        // Late spectators find out how it ended
+        let mut late = TestClient::connect(address);
+        late.send("spectate 1");
+        assert_eq!(late.receive(), "spectate 1 2 2");
+        assert_eq!(late.receive(), "state 0,0 1,0 0,1 1,1 0,2");
+        assert_eq!(late.receive(), "result X");
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn test_web_server() {
+        use std::net::TcpStream;
+
+        use tungstenite::{Message, WebSocket};
+        use web::{Request, Response};
+
+        fn send(socket: &mut WebSocket<TcpStream>, request: Request) {
+            let json = serde_json::to_string(&request).unwrap();
+            socket.send(Message::Text(json)).unwrap();
+        }
+
+        fn receive(socket: &mut WebSocket<TcpStream>) -> Response {
+            match socket.read().unwrap() {
+                Message::Text(json) => serde_json::from_str(&json).unwrap(),
+                message => panic!("Unexpected message {message:?}"),
+            }
+        }
+
+        let server = web::WebServer::bind("127.0.0.1:0", DEFAULT_IDLE_TIMEOUT).unwrap();
+        let address = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        let mut clients: Vec<_> = (0..2)
+            .map(|_| {
+                let stream = TcpStream::connect(address).unwrap();
+                tungstenite::client(format!("ws://{address}"), stream)
+                    .unwrap()
+                    .0
+            })
+            .collect();
+
+        // The request format is what browsers will be sending
+        let create = r#"{"type": "create", "dim": 2, "players": 2}"#;
+        clients[0].send(Message::Text(create.to_string())).unwrap();
+        assert_eq!(receive(&mut clients[0]), Response::Created { game: 1 });
+
+        for (i, symbol) in [(0, 'X'), (1, 'O')] {
+            send(&mut clients[i], Request::Join { game: Some(1) });
+            let Response::Joined {
+                game: 1,
+                player,
+                dim: 2,
+                players: 2,
+                ..
+            } = receive(&mut clients[i])
+            else {
+                panic!("Expected to join game 1");
+            };
+            assert_eq!(player, symbol);
+        }
+        // One update for each player joining
+        assert!(matches!(receive(&mut clients[0]), Response::Update { .. }));
+        for client in &mut clients {
+            assert_eq!(
+                receive(client),
+                Response::Update {
+                    game: 1,
+                    moves: vec![],
+                    current_player: 'X'
+                }
+            );
+        }
+
+        send(&mut clients[1], Request::Place { coords: vec![1, 1] });
+        assert_eq!(
+            receive(&mut clients[1]),
+            Response::Error {
+                message: "Not your turn".to_string()
+            }
+        );
+
+        let moves = [
+            (0, [0, 0]),
+            (1, [1, 0]),
+            (0, [0, 1]),
+            (1, [1, 1]),
+            (0, [0, 2]),
+        ];
+        for (seat, coords) in moves {
+            send(
+                &mut clients[seat],
+                Request::Place {
+                    coords: coords.to_vec(),
+                },
+            );
+            for client in &mut clients {
+                assert!(matches!(receive(client), Response::Update { .. }));
+            }
+        }
+        for client in &mut clients {
+            assert_eq!(
+                receive(client),
+                Response::Outcome {
+                    game: 1,
+                    winner: Some('X')
+                }
+            );
+        }
+    }
+
+    /// The command for the mock engine in `examples`, which `cargo test`
+    /// builds next to the directory the tests are in.
+    fn mock_engine() -> String {
+        let tests = std::env::current_exe().unwrap();
+        let path = tests
+            .parent()
+            .and_then(std::path::Path::parent)
+            .unwrap()
+            .join("examples")
+            .join(format!("mock_engine{}", std::env::consts::EXE_SUFFIX));
+        assert!(path.exists(), "Build the examples to run this test");
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_engine() {
+        let mut engines: Vec<_> = (0..2)
+            .map(|_| engine::Engine::spawn(&mock_engine()).unwrap())
+            .collect();
+
+        let mut game = Game::new(3, 2);
+        for engine in &mut engines {
+            engine.new_game(&game).unwrap();
+        }
+        let movetime = std::time::Duration::from_millis(10);
+        for turn in 0.. {
+            let player = game.current_player();
+            let coords = engines[turn % 2].best_move(&game, movetime).unwrap();
+            assert_eq!(Some(&coords), game.hint(player).as_ref());
+            game.place_piece(Piece::new(player), coords).unwrap();
+            if game.outcome().is_some() {
+                break;
+            }
+        }
+        assert!(game.moves().count() > 1);
+        drop(engines);
+
+        // Engines that take too long are given up on, and killed when they
+        // don't exit
+        let start = std::time::Instant::now();
+        let mut engine =
+            engine::Engine::spawn(&format!("{} --delay 60000", mock_engine())).unwrap();
+        engine.new_game(&game).unwrap();
+        let error = engine.best_move(&Game::new(2, 2), movetime).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+        drop(engine);
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_match() {
+        let result = play_match(&mut strategy::Hint, &mut strategy::Hint, 2, 4).unwrap();
+        assert_eq!(result.games(), 4);
+        // The first player always wins when both play the hints
+        assert_eq!((result.wins, result.losses), (2, 2));
+        assert_eq!(result.average_length(), 7.0);
+        assert_eq!(result.elo_difference(), 0.0);
+
+        let result = MatchResult {
+            wins: 6,
+            draws: 2,
+            losses: 2,
+            moves: 0,
+        };
+        assert_eq!(result.score(), 0.7);
+        assert_eq!(result.elo_difference().round(), 147.0);
+        let (low, high) = result.elo_interval();
+        assert!(low < 0.0 && high > 300.0);
+
+        // Winning every game doesn't make the difference infinite
+        let result = MatchResult {
+            wins: 0,
+            draws: 0,
+            losses: 10,
+            moves: 0,
+        };
+        assert_eq!(result.elo_difference().round(), -512.0);
+        let (low, high) = result.elo_interval();
+        assert!(low.is_finite() && low <= high && high < 0.0);
+    }
+
+    #[test]
+    fn test_round_robin() {
+        use tournament::{Format, Outcome, Tournament};
+
+        let mut tournament = Tournament::new(5, vec![2, 3], Format::RoundRobin);
+        // Each of the 10 pairs plays a game on each board
+        assert_eq!(tournament.pairings().len(), 20);
+        for a in 0..5 {
+            for b in a + 1..5 {
+                let games = tournament
+                    .pairings()
+                    .iter()
+                    .filter(|p| (p.first, p.second) == (a, b) || (p.first, p.second) == (b, a));
+                // Taking turns going first
+                assert_eq!(games.map(|p| p.first).sorted().collect_vec(), [a, b]);
+            }
+        }
+
+        // Lower-numbered participants always win
+        while let Some(game) = tournament.next_game() {
+            let pairing = &tournament.pairings()[game];
+            tournament.record(game, Outcome::Win(pairing.first.min(pairing.second)));
+        }
+        let standings = tournament.standings();
+        assert_eq!(
+            standings.iter().map(|s| s.participant).collect_vec(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(standings[0].points, 8.0);
+        assert_eq!(standings[4].losses, 8);
+    }
+
+    #[test]
+    fn test_swiss() {
+        use tournament::{Format, Outcome, Tournament};
+
+        let mut tournament = Tournament::new(5, vec![2], Format::Swiss(3));
+        for round in 1..=3 {
+            let games = tournament
+                .pairings()
+                .iter()
+                .positions(|p| p.round == round)
+                .collect_vec();
+            // Everyone but the one with a bye plays once a round
+            assert_eq!(games.len(), 2);
+            for game in games {
+                let pairing = &tournament.pairings()[game];
+                tournament.record(game, Outcome::Win(pairing.first.min(pairing.second)));
+            }
+        }
+        assert_eq!(tournament.next_game(), None);
+
+        // Nobody plays anyone twice or gets more than one bye
+        let pairs = tournament
+            .pairings()
+            .iter()
+            .map(|p| (p.first.min(p.second), p.first.max(p.second)))
+            .collect_vec();
+        assert!(pairs.iter().all_unique());
+        let standings = tournament.standings();
+        assert!(standings.iter().all(|s| s.byes <= 1));
+        assert_eq!(
+            standings
+                .iter()
+                .map(|s| s.wins + s.losses + s.byes)
+                .sum::<u32>(),
+            15
+        );
+        assert_eq!(standings[0].participant, 0);
+    }
+
+    #[test]
+    fn test_ratings() {
+        use ratings::{Ratings, INITIAL_RATING};
+
+        let mut ratings = Ratings::new();
+        let game = setup_3d_winner(Player::new('X'), Player::new('O'));
+        assert_eq!(ratings.record(&game, &["alice", "bob"]), game.outcome());
+        let alice = ratings.get("alice").unwrap();
+        assert_eq!(alice.rating, INITIAL_RATING + 16.0);
+        assert_eq!((alice.wins, alice.losses), (1, 0));
+        assert_eq!(ratings.get("bob").unwrap().rating, INITIAL_RATING - 16.0);
+
+        // Unfinished games don't count
+        assert_eq!(ratings.record(&Game::new(2, 2), &["alice", "bob"]), None);
+        assert_eq!(ratings.get("alice").unwrap().games(), 1);
+
+        // With three players, the winner beats both of the others, who draw
+        let mut game = Game::new(2, 3);
+        let moves = [[0, 0], [1, 0], [2, 0], [0, 1], [1, 1], [2, 1], [0, 2]];
+        for coords in moves {
+            let player = game.current_player();
+            game.place_piece(Piece::new(player), coords.to_vec().into())
+                .unwrap();
+        }
+        ratings.record(&game, &["carol", "alice", "bob"]);
+        let leaderboard = ratings.leaderboard();
+        let names = leaderboard.iter().map(|(name, _)| *name).collect_vec();
+        assert_eq!(names, ["carol", "alice", "bob"]);
+        // Bob gained a bit from drawing with Alice
+        assert!(leaderboard[2].1.rating > INITIAL_RATING - 16.0 - 8.0);
+        assert_eq!(ratings.get("carol").unwrap().wins, 1);
+    }
+
+    #[test]
+    fn test_clock() {
+        use clock::{Clock, TimeControl};
+        use std::time::Duration;
+
+        let (x, o) = (Player::new('X'), Player::new('O'));
+        let control = TimeControl::Increment {
+            total: Duration::from_secs(60),
+            increment: Duration::from_secs(2),
+        };
+        let mut clock = Clock::new(control, &[x, o]);
+        clock.start(x);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(clock.remaining(x) < Duration::from_secs(60));
+        assert_eq!(clock.remaining(o), Duration::from_secs(60));
+        clock.stop();
+        assert!(clock.remaining(x) > Duration::from_secs(61));
+        assert!(clock.time_for_move(x) < Duration::from_secs(6));
+
+        clock.set_remaining(o, Duration::from_secs(65));
+        assert_eq!(format!("{clock:#}").lines().last(), Some("O 1:05.0"));
+        clock.set_remaining(o, Duration::from_millis(299_960));
+        assert_eq!(format!("{clock:#}").lines().last(), Some("O 5:00.0"));
+
+        // Running out of time
+        let mut clock = Clock::new(TimeControl::PerMove(Duration::from_millis(5)), &[x, o]);
+        clock.start(x);
+        assert_eq!(clock.out_of_time(), None);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(clock.out_of_time(), Some(x));
+        // The time resets for each move
+        clock.stop();
+        assert_eq!(clock.remaining(x), Duration::from_millis(5));
+
+        // Increments past the most time a clock can hold
+        let control = TimeControl::Increment {
+            total: Duration::MAX,
+            increment: Duration::MAX,
+        };
+        let mut clock = Clock::new(control, &[x, o]);
+        clock.start(x);
+        clock.stop();
+        assert_eq!(clock.remaining(x), Duration::MAX);
+        assert!(clock.time_for_move(x) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_strategies() {
+        use strategy::{Center, Greedy, Random, Strategy};
+
+        let (x, o) = (Player::new('X'), Player::new('O'));
+        let mut game = Game::new(2, 2);
+        let mut center = Center::with_seed(1);
+        assert_eq!(center.choose(&game, x).unwrap(), VecDeque::from([1, 1]));
+        game.place_piece(Piece::new(x), vec![1, 1].into()).unwrap();
+        // Then a corner, which is on more lines than the edges
+        let coords = center.choose(&game, o).unwrap();
+        assert!(coords.iter().all(|x| *x != 1));
+
+        game.place_piece(Piece::new(o), vec![0, 0].into()).unwrap();
+        game.place_piece(Piece::new(x), vec![0, 1].into()).unwrap();
+        // O has to block X's column
+        let mut greedy = Greedy::with_seed(1);
+        assert_eq!(greedy.choose(&game, o).unwrap(), VecDeque::from([2, 1]));
+        game.place_piece(Piece::new(o), vec![2, 1].into()).unwrap();
+        game.place_piece(Piece::new(x), vec![2, 2].into()).unwrap();
+        // O can win instead of blocking
+        assert_eq!(greedy.choose(&game, o).unwrap(), VecDeque::from([1, 0]));
+
+        // Random moves are always legal, and the same with the same seed
+        let play = |seed| {
+            let mut game = Game::new(3, 2);
+            play_game(
+                &mut game,
+                &mut [&mut Random::with_seed(seed), &mut Greedy::with_seed(seed)],
+            )
+            .unwrap();
+            let moves = game.moves().collect_vec();
+            moves
+        };
+        assert_eq!(play(7), play(7));
+    }
+
+    #[test]
+    fn test_ultimate() {
+        use ultimate::{MoveError, Ultimate};
+
+        let (x, o) = (Player::new('X'), Player::new('O'));
+        let mut ultimate = Ultimate::new(2, 2);
+        let mut play = |board: [usize; 2], cell: [usize; 2]| {
+            ultimate.make_move(board.to_vec().into(), cell.to_vec().into())
+        };
+        play([0, 0], [1, 1]).unwrap();
+        // O was sent to the middle board
+        assert_eq!(play([0, 0], [0, 0]), Err(MoveError::WrongBoard));
+        play([1, 1], [0, 0]).unwrap();
+        play([0, 0], [2, 2]).unwrap();
+        play([2, 2], [1, 1]).unwrap();
+        play([1, 1], [1, 0]).unwrap();
+        play([1, 0], [1, 1]).unwrap();
+        play([1, 1], [1, 2]).unwrap();
+        play([1, 2], [1, 1]).unwrap();
+        assert_eq!(play([1, 1], [1, 0]), Err(MoveError::Occupied));
+        // X wins the middle board, so O can play anywhere else
+        play([1, 1], [1, 1]).unwrap();
+        assert_eq!(
+            ultimate.meta().moves().collect_vec(),
+            [(vec![1, 1].into(), x)]
+        );
+        assert_eq!(ultimate.sent_to(), None);
+        assert_eq!(
+            ultimate.make_move(vec![1, 1].into(), vec![2, 2].into()),
+            Err(MoveError::Closed)
+        );
+        // The other 8 boards, less the 5 pieces on them
+        assert_eq!(ultimate.legal_moves().count(), 8 * 9 - 5);
+        let highlights = ultimate.highlights();
+        assert_eq!(
+            highlights.get(&VecDeque::from([1, 1, 1, 0])),
+            Some(&Highlight::WinningLine)
+        );
+        assert!(!highlights.values().any(|h| *h == Highlight::Target));
+
+        // Taking the move back takes the claim back too
+        assert_eq!(
+            ultimate.unmake_move(),
+            Some((vec![1, 1].into(), vec![1, 1].into(), x))
+        );
+        assert_eq!(ultimate.meta().moves().count(), 0);
+        assert_eq!(ultimate.sent_to(), Some(vec![1, 1].into()));
+        let display = ultimate.display_with(&DisplayOptions {
+            highlights: ultimate.highlights(),
+            plain: true,
+            ..Default::default()
+        });
+        assert_eq!(display.matches('+').count(), 6);
+        assert_eq!(display.matches('X').count(), 4);
+        assert_eq!(display.matches('O').count(), 4);
+
+        // Games always end, with any number of dimensions
+        for dim in 1..=3 {
+            let mut ultimate = Ultimate::new(dim, 2);
+            loop {
+                let Some((board, cell)) = ultimate.legal_moves().next() else {
+                    break;
+                };
+                ultimate.make_move(board, cell).unwrap();
+            }
+            assert!(ultimate.outcome().is_some());
+            if let Some(GameOutcome::Win(player)) = ultimate.outcome() {
+                assert!(ultimate.meta().check_win(player));
+            }
+            assert!(ultimate
+                .moves()
+                .all(|(_, _, player)| player == x || player == o));
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        use record::{Record, RecordError};
+
+        let text = "[Date \"2024.03.09\"]\n\
+                    [Players \"alice bob\"]\n\
+                    [Dim \"2\"]\n\
+                    [Width \"3\"]\n\
+                    [Result \"draw\"]\n\
+                    \n\
+                    1. 1,1 0,0 2. 0,2 2,0 3. 1,0 1,2 4. 2,1 0,1 5. 2,2 draw\n";
+        let record: Record = text.parse().unwrap();
+        assert_eq!(
+            record.names,
+            [Some("alice".to_string()), Some("bob".to_string())]
+        );
+        assert_eq!(record.date.as_deref(), Some("2024.03.09"));
+        assert_eq!(record.moves.len(), 9);
+        assert_eq!(record.moves[2], VecDeque::from([0, 2]));
+        assert_eq!(record.to_string(), text);
+        let game = record.game().unwrap();
+        assert_eq!(game.outcome(), Some(GameOutcome::Draw));
+        assert_eq!(Record::new(&game).moves, record.moves);
+
+        // Games in higher dimensions, with long lines of moves, read back the
+        // same
+        let game = setup_3d_winner(Player::new('O'), Player::new('X'));
+        let mut record = Record::new(&game);
+        record.names[1] = Some("bob".to_string());
+        let text = record.to_string();
+        assert!(text.contains("[Players \"? bob\"]"));
+        assert!(text.lines().all(|line| line.len() <= 79));
+        assert_eq!(text.parse::<Record>().unwrap(), record);
+        assert_eq!(record.game().unwrap().board, game.board);
+
+        // Archives hold one record after another
+        let archive = format!("{text}\n{text}");
+        assert_eq!(
+            Record::parse_all(&archive).unwrap(),
+            [record.clone(), record]
+        );
+
+        let text = "[Players \"? ?\"]\n[Dim \"2\"]\n[Width \"3\"]\n\n";
+        let parse = |moves: &str| format!("{text}{moves}").parse::<Record>();
+        assert_eq!(parse("1. 0,0 1,1 *").unwrap().result, None);
+        assert_eq!(
+            parse("1. 0,0 x,1"),
+            Err(RecordError::InvalidMove("x,1".to_string()))
+        );
+        assert_eq!(
+            parse("1. 0,0 X 1,1"),
+            Err(RecordError::InvalidMove("X".to_string()))
+        );
+        assert_eq!(
+            parse("1. 0,0 0,0").unwrap().game(),
+            Err(RecordError::IllegalMove(2))
+        );
+        let huge = "[Players \"? ?\"]\n[Dim \"20\"]\n[Width \"21\"]\n\n*";
+        assert_eq!(
+            huge.parse::<Record>().unwrap().game(),
+            Err(RecordError::TooManyDimensions)
+        );
+        assert_eq!(
+            "[Dim \"2\"]\n\n1. 0,0".parse::<Record>(),
+            Err(RecordError::MissingTag("Players"))
+        );
+        assert_eq!(
+            "Dim 2\n".parse::<Record>(),
+            Err(RecordError::InvalidTag("Dim 2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_notation() {
+        let game = Game::from_notation("2 3 2 X X3.O4.").unwrap();
+        assert_eq!(game.current_player(), Player::new('X'));
+        assert!(!game.is_legal(&vec![0, 0].into()));
+        assert!(!game.is_legal(&vec![1, 1].into()));
+        assert_eq!(game.legal_moves().count(), 7);
+        assert_eq!(game.to_notation(), "2 3 2 X X3.O4.");
+        assert_eq!(
+            Game::from_notation("2 3 2 O X8.").unwrap().to_notation(),
+            "2 3 2 O X8."
+        );
+
+        // Positions read back the same, and a win in one is still a win
+        let game = setup_3d_winner(Player::new('O'), Player::new('X'));
+        let notation = game.to_notation();
+        let read = Game::from_notation(&notation).unwrap();
+        assert_eq!(read.to_notation(), notation);
+        assert_eq!(read.board, game.board);
+        assert!(read.check_win(Player::new('O')));
+        let game = setup_4d_both_win(Player::new('X'), Player::new('O'));
+        assert_eq!(
+            Game::from_notation(&game.to_notation()).unwrap().board,
+            game.board
+        );
+
+        assert_eq!(
+            Game::from_notation("1 2 3 F XO"),
+            Ok(Game::new(1, 3)).map(|mut game| {
+                game.make_move(vec![0].into()).unwrap();
+                game.make_move(vec![1].into()).unwrap();
+                game
+            })
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X"),
+            Err(NotationError::Malformed)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 F 9."),
+            Err(NotationError::Malformed)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X 0X9."),
+            Err(NotationError::Malformed)
+        );
+        assert_eq!(
+            Game::from_notation("2 4 2 X 16."),
+            Err(NotationError::UnsupportedWidth)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X 8."),
+            Err(NotationError::WrongCellCount)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X X99."),
+            Err(NotationError::WrongCellCount)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X .18446744073709551615."),
+            Err(NotationError::WrongCellCount)
+        );
+        assert_eq!(
+            Game::from_notation("30 31 2 X ."),
+            Err(NotationError::TooManyDimensions)
+        );
+        assert_eq!(
+            Game::from_notation("2 3 2 X X8."),
+            Err(NotationError::ImpossibleTurn)
+        );
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let mut game = Game::new(3, 2);
+        assert_eq!(game.legal_moves().count(), 64);
+        assert!(game.is_legal(&vec![3, 0, 2].into()));
+
+        game.place_piece(Piece::new(Player::new('X')), vec![3, 0, 2].into())
+            .unwrap();
+        game.place_piece(Piece::new(Player::new('O')), vec![0, 0, 0].into())
+            .unwrap();
+        let moves = game.legal_moves().collect_vec();
+        assert_eq!(moves.len(), 62);
+        assert_eq!(moves[0], VecDeque::from([0, 0, 1]));
+        assert!(moves.iter().all(|coords| game.is_legal(coords)));
+        assert!(!game.is_legal(&vec![3, 0, 2].into()));
+        assert_eq!(
+            game.piece(&vec![3, 0, 2].into()),
+            Some(&Piece::new(Player::new('X')))
+        );
+
+        // Out of bounds, or the wrong number of coordinates
+        assert!(!game.is_legal(&vec![4, 0, 0].into()));
+        assert!(!game.is_legal(&vec![0, 0].into()));
+        assert!(!game.is_legal(&vec![0, 0, 0, 0].into()));
+        assert_eq!(game.piece(&vec![0, 0].into()), None);
+
+        game.undo();
+        assert_eq!(game.legal_moves().count(), 63);
+    }
+
+    #[test]
+    fn test_make_unmake_move() {
+        use strategy::{Random, Strategy};
+
+        // Taking back any move in any game leaves everything exactly how it
+        // was, including the hash
+        for seed in 1..=50 {
+            let mut random = Random::with_seed(seed);
+            let dim = seed as usize % 3 + 1;
+            let players = seed as u32 % 3 + 1;
+            let mut game = Game::new(dim, players);
+            let mut hashes = vec![game.position_hash()];
+            while let Ok(coords) = random.choose(&game, game.current_player()) {
+                let before = game.clone();
+                game.make_move(coords.clone()).unwrap();
+                assert_ne!(game.position_hash(), before.position_hash());
+                assert_eq!(
+                    game.unmake_move(),
+                    Some((coords.clone(), before.current_player()))
+                );
+                assert_eq!(game, before);
+
+                game.make_move(coords).unwrap();
+                hashes.push(game.position_hash());
+            }
+
+            while game.unmake_move().is_some() {
+                hashes.pop();
+                assert_eq!(game.position_hash(), *hashes.last().unwrap());
+            }
+            assert_eq!(game, Game::new(dim, players));
+        }
+
+        // The hash only depends on where the pieces are
+        let play = |moves: [[usize; 2]; 3]| {
+            let mut game = Game::new(2, 2);
+            for coords in moves {
+                game.make_move(coords.to_vec().into()).unwrap();
+            }
+            game.position_hash()
+        };
+        assert_eq!(
+            play([[0, 0], [1, 1], [2, 2]]),
+            play([[2, 2], [1, 1], [0, 0]])
+        );
+        assert_ne!(
+            play([[0, 0], [1, 1], [2, 2]]),
+            play([[1, 1], [0, 0], [2, 2]])
+        );
+    }
+
+    #[test]
+    fn test_human_and_scripted() {
+        use strategy::{Human, Scripted, Strategy};
+
+        // People enter coordinates in their own order, and get asked again
+        // until the move can be made
+        let game = Game::new(3, 2);
+        let mut output = Vec::new();
+        let mut human = Human::new("a b\n9 9 9\n0 1 2\n".as_bytes(), &mut output);
+        assert_eq!(
+            human.choose(&game, Player::new('X')).unwrap(),
+            game.map_player_coords(vec![0, 1, 2].into())
+        );
+        assert!(human.choose(&game, Player::new('X')).is_err());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("try again").count(), 2);
+        let mut output = Vec::new();
+        let mut human = Human::new("quit\n".as_bytes(), &mut output)
+            .with_prompt(|game, player| format!("{}{}? ", game.dim(), player.symbol()));
+        let error = human.choose(&game, Player::new('X')).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+        assert_eq!(output, b"3X? ");
+
+        // Lines can come from anywhere, a line at a time
+        let mut lines = ["1 1", "1 1", "0 0"].into_iter().map(String::from);
+        let input = strategy::Lines::new(move || Ok(lines.next()));
+        let mut human = Human::new(input, std::io::sink());
+        let mut game = Game::new(2, 2);
+        let coords = human.choose(&game, Player::new('X')).unwrap();
+        game.make_move(coords).unwrap();
+        assert_eq!(human.choose(&game, Player::new('O')).unwrap(), vec![0, 0]);
+        let error = human.choose(&game, Player::new('O')).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        // Any seat can be played by anything
+        let mut game = Game::new(2, 2);
+        let mut output = Vec::new();
+        let mut scripted = Scripted::new([vec![0, 0].into(), vec![0, 1].into(), vec![0, 2].into()]);
+        let mut human = Human::new("2 2\n1 1\n".as_bytes(), &mut output);
+        let outcome = play_game(&mut game, &mut [&mut scripted, &mut human]).unwrap();
+        assert_eq!(outcome, GameOutcome::Win(Player::new('X')));
+        assert!(scripted.choose(&game, Player::new('X')).is_err());
+    }
+
+    #[test]
+    fn test_map_player_coords() {
+        let game = Game::new(3, 2);
+        assert_eq!(
+            game.map_player_coords(vec![0, 1, 2].into()),
+            VecDeque::from([0, 2, 1])
+        );
+
+        let game = Game::new(4, 2);
+        assert_eq!(
+            game.map_player_coords(vec![0, 1, 2, 3].into()),
+            VecDeque::from([0, 1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_3d_winning_line() {
+        let p0 = Player::new('X');
+        let p1 = Player::new('O');
+        let game = setup_3d_winner(p1, p0);
+
+        assert_eq!(game.winning_line(p0), None);
+        assert_eq!(
+            game.winning_line(p1),
+            Some(vec![
+                vec![0, 0, 0].into(),
+                vec![0, 1, 1].into(),
+                vec![0, 2, 2].into(),
+                vec![0, 3, 3].into(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display_highlighted() {
+        let mut game = Game::new(2, 2);
+        let p0 = Player::new('X');
+        game.place_piece(Piece::new(p0), vec![1, 2].into()).unwrap();
+
+        let highlighted = game.display_with(&DisplayOptions {
+            highlights: HashMap::from([(vec![1, 2].into(), Highlight::LastMove)]),
+            ..Default::default()
+        });
+        assert_ne!(highlighted, game.display(false));
+        assert!(highlighted.contains(&format!("\x1b[4m{}", Piece::new(p0))));
+    }
+
+    #[test]
+    fn test_undo() {
+        let p0 = Player::new('X');
+        let p1 = Player::new('O');
+        let mut game = setup_3d_winner(p0, p1);
+        assert!(game.check_win(p0));
+
+        assert_eq!(game.undo(), Some((vec![0, 2, 2].into(), p0)));
+        assert!(!game.check_win(p0));
+        assert_eq!(game.current_player(), Player::new('O'));
+        assert!(game
+            .board
+            .get(vec![0, 2, 2].into())
+            .unwrap()
+            .player
+            .is_none());
+
+        // Replaying the move wins again
+        game.place_piece(Piece::new(p0), vec![0, 2, 2].into())
+            .unwrap();
+        assert!(game.check_win(p0));
+    }
+
+    #[test]
+    fn test_undo_empty() {
+        let mut game = Game::new(2, 2);
+        assert_eq!(game.undo(), None);
+    }
+
+    #[test]
+    fn test_hint_wins_then_blocks() {
+        let mut game = Game::new(2, 2);
+        let p0 = Player::new('X');
+        let p1 = Player::new('O');
+
+        game.place_piece(Piece::new(p0), vec![0, 0].into()).unwrap();
+        game.place_piece(Piece::new(p1), vec![0, 2].into()).unwrap();
+        game.place_piece(Piece::new(p0), vec![1, 1].into()).unwrap();
+        game.place_piece(Piece::new(p1), vec![2, 0].into()).unwrap();
+
+        // X can win immediately
+        assert_eq!(game.hint(p0), Some(vec![2, 2].into()));
+        // O can't win yet, so it has to block X's diagonal
+        assert_eq!(game.hint(p1), Some(vec![2, 2].into()));
+    }
+
+    #[test]
+    fn test_layout_matches_display() {
+        for dim in 1..=5 {
+            for hide_padding in [false, true] {
+                // Give every cell a different (single character) symbol
+                let mut board = Board::<char>::new(vec![dim + 1; dim], '.');
+                let layout = board.layout(board::Direction::Horizontal, hide_padding);
+                for (i, (coords, _)) in layout.cells.iter().enumerate() {
+                    board[coords.clone()] = char::from_u32(0x100 + i as u32).unwrap().into();
+                }
+
+                let display = board.display(board::Direction::Horizontal, hide_padding);
+                let lines: Vec<Vec<char>> = display.lines().map(|l| l.chars().collect()).collect();
+
+                assert_eq!(lines.len(), layout.height);
+                assert_eq!(lines.iter().map(|l| l.len()).max(), Some(layout.width));
+                for (coords, (line, column)) in &layout.cells {
+                    assert_eq!(Some(&lines[*line][*column]), board.get(coords.clone()));
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_2d_no_wraparound_win_0() {
         let mut game = Game::new(2, 2);
@@ -229,7 +1608,7 @@ mod test {
 
         assert!(!game.check_win(p0));
     }
-    
+
     #[test]
     fn test_2d_no_wraparound_win_1() {
         let mut game = Game::new(2, 1);
@@ -0,0 +1,453 @@
+//! The games hosted by a server, and who's playing in each of them. This is
+//! shared by [`Server`](crate::Server) and the web server, which translate
+//! between their own messages and [`Action`]s and [`Notice`]s.
+//!
+//! Players who get disconnected keep their seat, and can take it back with
+//! the token they got when they joined. Games nobody has done anything in for
+//! a while are closed.
+
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    time::{Duration, Instant},
+};
+
+use super::{board::Idx, Game, GameOutcome, Piece, Player};
+
+/// How long a game can go without any moves or players joining before it's
+/// closed, unless the server says otherwise.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// The most dimensions a game can have, since boards get big very quickly
+/// (a 6D board already has over 100,000 cells).
+pub const MAX_DIM: usize = 6;
+
+/// Something a client wants to do.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Action {
+    Create {
+        dim: usize,
+        players: u32,
+    },
+    List,
+    /// Takes a free seat in the game, or the first game with one.
+    Join(Option<u32>),
+    /// Takes back a seat after being disconnected.
+    Rejoin {
+        game: u32,
+        token: u64,
+    },
+    Spectate(u32),
+    /// Coordinates in the order players enter them in
+    Place(Idx),
+}
+
+/// Something a client needs to know.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Notice {
+    Created(u32),
+    Games(Vec<GameInfo>),
+    Joined {
+        game: u32,
+        player: Player,
+        dim: usize,
+        players: u32,
+        /// For taking the seat back with [`Action::Rejoin`]
+        token: u64,
+    },
+    Spectating {
+        game: u32,
+        dim: usize,
+        players: u32,
+    },
+    /// Every move played so far (in the order players enter coordinates in),
+    /// oldest first.
+    Update {
+        game: u32,
+        moves: Vec<(Idx, Player)>,
+        current_player: Player,
+    },
+    Outcome {
+        game: u32,
+        outcome: GameOutcome,
+    },
+    Error(String),
+}
+
+/// A summary of a hosted game, for listing them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "web", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameInfo {
+    pub id: u32,
+    pub dim: usize,
+    pub players: u32,
+    /// Seats nobody has taken yet
+    pub open_seats: usize,
+    pub spectators: usize,
+}
+
+/// Something that happened on one of the connections.
+pub(crate) enum Event {
+    Connected(usize, Sender<Notice>),
+    Action(usize, Action),
+    /// A message from the connection that couldn't be understood
+    Invalid(usize, String),
+    Disconnected(usize),
+}
+
+#[derive(Default)]
+struct Seat {
+    /// Set once someone takes the seat
+    token: Option<u64>,
+    /// `None` while the seat's player is disconnected
+    connection: Option<usize>,
+}
+
+/// A game being played on the server.
+struct Table {
+    game: Game,
+    seats: Vec<Seat>,
+    spectators: Vec<usize>,
+    last_active: Instant,
+}
+
+impl Table {
+    /// Everyone who hears about the game.
+    fn members(&self) -> impl Iterator<Item = usize> + '_ {
+        self.seats
+            .iter()
+            .filter_map(|seat| seat.connection)
+            .chain(self.spectators.iter().copied())
+    }
+
+    fn info(&self, id: u32) -> GameInfo {
+        GameInfo {
+            id,
+            dim: self.game.dim(),
+            players: self.game.players().len() as u32,
+            open_seats: self
+                .seats
+                .iter()
+                .filter(|seat| seat.token.is_none())
+                .count(),
+            spectators: self.spectators.len(),
+        }
+    }
+}
+
+/// How a connection is taking part in a game.
+#[derive(Clone, Copy)]
+enum Role {
+    Player(usize),
+    Spectator,
+}
+
+pub(crate) struct Lobby {
+    connections: HashMap<usize, Sender<Notice>>,
+    tables: BTreeMap<u32, Table>,
+    /// The game each connection is in
+    members: HashMap<usize, (u32, Role)>,
+    next_id: u32,
+    idle_timeout: Duration,
+}
+
+impl Lobby {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            connections: HashMap::new(),
+            tables: BTreeMap::new(),
+            members: HashMap::new(),
+            next_id: 0,
+            idle_timeout,
+        }
+    }
+
+    /// Hosts `game`, returning its ID.
+    pub fn create(&mut self, game: Game) -> u32 {
+        self.next_id += 1;
+        let table = Table {
+            seats: game.players().iter().map(|_| Seat::default()).collect(),
+            game,
+            spectators: Vec::new(),
+            last_active: Instant::now(),
+        };
+        self.tables.insert(self.next_id, table);
+        self.next_id
+    }
+
+    /// Handles events until every connection (and whatever accepts them) is
+    /// gone, closing idle games along the way.
+    pub fn run(mut self, events: Receiver<Event>) {
+        let interval = self.idle_timeout.min(Duration::from_secs(1));
+        loop {
+            match events.recv_timeout(interval) {
+                Ok(Event::Connected(id, sender)) => {
+                    self.connections.insert(id, sender);
+                }
+                Ok(Event::Action(id, action)) => self.handle(id, action),
+                Ok(Event::Invalid(id, message)) => self.error(id, &message),
+                Ok(Event::Disconnected(id)) => self.leave(id),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            self.close_idle();
+        }
+    }
+
+    fn send(&self, id: usize, notice: Notice) {
+        if let Some(sender) = self.connections.get(&id) {
+            // The connection will tell us if it's gone
+            let _ = sender.send(notice);
+        }
+    }
+
+    fn error(&self, id: usize, message: &str) {
+        self.send(id, Notice::Error(message.to_string()));
+    }
+
+    fn broadcast(&self, game_id: u32, notice: Notice) {
+        for id in self.tables[&game_id].members() {
+            self.send(id, notice.clone());
+        }
+    }
+
+    fn handle(&mut self, id: usize, action: Action) {
+        match action {
+            Action::Create { dim, players } => {
+                if !(1..=3).contains(&players) || !(1..=MAX_DIM).contains(&dim) {
+                    return self.error(
+                        id,
+                        &format!("Games need 1 to 3 players and 1 to {MAX_DIM} dimensions"),
+                    );
+                }
+                let game_id = self.create(Game::new(dim, players));
+                self.send(id, Notice::Created(game_id));
+            }
+            Action::List => {
+                let games = self
+                    .tables
+                    .iter()
+                    .map(|(game_id, table)| table.info(*game_id))
+                    .collect();
+                self.send(id, Notice::Games(games));
+            }
+            Action::Join(game_id) => self.join(id, game_id),
+            Action::Rejoin { game, token } => self.rejoin(id, game, token),
+            Action::Spectate(game_id) => self.spectate(id, game_id),
+            Action::Place(coords) => self.place(id, coords),
+        }
+    }
+
+    fn join(&mut self, id: usize, game_id: Option<u32>) {
+        if self.members.contains_key(&id) {
+            return self.error(id, "Already in a game");
+        }
+
+        let open_seat = |table: &Table| table.seats.iter().position(|seat| seat.token.is_none());
+        let (game_id, seat) = match game_id {
+            Some(game_id) => {
+                let Some(table) = self.tables.get(&game_id) else {
+                    return self.error(id, "No such game");
+                };
+                let Some(seat) = open_seat(table) else {
+                    return self.error(id, "The game is full");
+                };
+                (game_id, seat)
+            }
+            None => {
+                let Some((game_id, seat)) = self
+                    .tables
+                    .iter()
+                    .find_map(|(game_id, table)| Some((*game_id, open_seat(table)?)))
+                else {
+                    return self.error(id, "No games with free seats");
+                };
+                (game_id, seat)
+            }
+        };
+
+        let token = new_token();
+        self.tables.get_mut(&game_id).unwrap().seats[seat] = Seat {
+            token: Some(token),
+            connection: Some(id),
+        };
+        self.seat(id, game_id, seat);
+    }
+
+    fn rejoin(&mut self, id: usize, game_id: u32, token: u64) {
+        if self.members.contains_key(&id) {
+            return self.error(id, "Already in a game");
+        }
+        let Some(table) = self.tables.get_mut(&game_id) else {
+            return self.error(id, "No such game");
+        };
+        let Some(seat) = table
+            .seats
+            .iter()
+            .position(|seat| seat.token == Some(token))
+        else {
+            return self.error(id, "Wrong token");
+        };
+        if table.seats[seat].connection.is_some() {
+            return self.error(id, "That seat is already taken");
+        }
+
+        table.seats[seat].connection = Some(id);
+        self.seat(id, game_id, seat);
+    }
+
+    /// Lets everyone know `id` has taken their seat.
+    fn seat(&mut self, id: usize, game_id: u32, seat: usize) {
+        self.members.insert(id, (game_id, Role::Player(seat)));
+
+        let table = self.tables.get_mut(&game_id).unwrap();
+        table.last_active = Instant::now();
+        let table = &self.tables[&game_id];
+        self.send(
+            id,
+            Notice::Joined {
+                game: game_id,
+                player: table.game.players()[seat],
+                dim: table.game.dim(),
+                players: table.game.players().len() as u32,
+                token: table.seats[seat].token.unwrap(),
+            },
+        );
+        self.broadcast(game_id, update(game_id, &table.game));
+    }
+
+    fn spectate(&mut self, id: usize, game_id: u32) {
+        if self.members.contains_key(&id) {
+            return self.error(id, "Already in a game");
+        }
+        let Some(table) = self.tables.get_mut(&game_id) else {
+            return self.error(id, "No such game");
+        };
+
+        table.spectators.push(id);
+        self.members.insert(id, (game_id, Role::Spectator));
+
+        let table = &self.tables[&game_id];
+        self.send(
+            id,
+            Notice::Spectating {
+                game: game_id,
+                dim: table.game.dim(),
+                players: table.game.players().len() as u32,
+            },
+        );
+        self.send(id, update(game_id, &table.game));
+        if let Some(outcome) = table.game.outcome() {
+            self.send(
+                id,
+                Notice::Outcome {
+                    game: game_id,
+                    outcome,
+                },
+            );
+        }
+    }
+
+    fn place(&mut self, id: usize, coords: Idx) {
+        let (game_id, seat) = match self.members.get(&id) {
+            Some(&(game_id, Role::Player(seat))) => (game_id, seat),
+            Some((_, Role::Spectator)) => return self.error(id, "Spectators can't place pieces"),
+            None => return self.error(id, "Join a game first"),
+        };
+        let table = self.tables.get_mut(&game_id).expect("Joined games exist");
+        let game = &mut table.game;
+
+        let player = game.current_player();
+        if table.seats.iter().any(|seat| seat.token.is_none()) {
+            return self.error(id, "Waiting for players");
+        }
+        if game.outcome().is_some() {
+            return self.error(id, "The game is over");
+        }
+        if player != game.players()[seat] {
+            return self.error(id, "Not your turn");
+        }
+        if coords.len() != game.dim() {
+            return self.error(id, "Wrong number of coordinates");
+        }
+
+        let coords = game.map_player_coords(coords);
+        if let Err(e) = game.place_piece(Piece::new(player), coords) {
+            return self.error(id, &e.to_string());
+        }
+        table.last_active = Instant::now();
+
+        let table = &self.tables[&game_id];
+        self.broadcast(game_id, update(game_id, &table.game));
+        if let Some(outcome) = table.game.outcome() {
+            self.broadcast(
+                game_id,
+                Notice::Outcome {
+                    game: game_id,
+                    outcome,
+                },
+            );
+        }
+    }
+
+    /// Forgets a connection that's gone. Its seat (if it had one) is kept for
+    /// it to rejoin.
+    fn leave(&mut self, id: usize) {
+        self.connections.remove(&id);
+        let Some((game_id, role)) = self.members.remove(&id) else {
+            return;
+        };
+        let Some(table) = self.tables.get_mut(&game_id) else {
+            return;
+        };
+
+        match role {
+            Role::Player(seat) => {
+                table.seats[seat].connection = None;
+                let player = table.game.players()[seat];
+                let message = format!(
+                    "Player {} was disconnected (they can rejoin)",
+                    player.symbol()
+                );
+                self.broadcast(game_id, Notice::Error(message));
+            }
+            Role::Spectator => table.spectators.retain(|spectator| *spectator != id),
+        }
+    }
+
+    fn close_idle(&mut self) {
+        let idle: Vec<_> = self
+            .tables
+            .iter()
+            .filter(|(_, table)| table.last_active.elapsed() >= self.idle_timeout)
+            .map(|(game_id, _)| *game_id)
+            .collect();
+
+        for game_id in idle {
+            let message = format!("Game {game_id} was closed for being idle");
+            self.broadcast(game_id, Notice::Error(message));
+
+            let table = self.tables.remove(&game_id).unwrap();
+            for id in table.members() {
+                self.members.remove(&id);
+            }
+        }
+    }
+}
+
+fn update(game_id: u32, game: &Game) -> Notice {
+    Notice::Update {
+        game: game_id,
+        moves: game
+            .moves()
+            .map(|(coords, player)| (game.map_player_coords(coords), player))
+            .collect(),
+        current_player: game.current_player(),
+    }
+}
+
+/// A token that's hard to guess (though not cryptographically secure).
+fn new_token() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
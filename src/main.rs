@@ -1,74 +1,447 @@
-use std::collections::VecDeque;
+mod client;
+mod input;
+mod save;
+mod seats;
+#[cfg(feature = "tui")]
+mod tui;
 
-use clap::Parser;
-use itertools::Itertools;
-use nd_tic_tac_toe::{Game, Piece};
+use std::{collections::HashMap, io::IsTerminal, time::Duration};
 
-fn main() {
-    // Clear the screen
-    print!("\x1B[2J\x1B[1;1H");
+use clap::{Parser, Subcommand};
+use client::Seat;
+use input::{Command, Input, View};
+use itertools::{EitherOrBoth, Itertools};
+use nd_tic_tac_toe::{
+    clock::{Clock, TimeControl},
+    engine::DEFAULT_MOVETIME,
+    play_game, play_match,
+    ratings::Ratings,
+    record::Record,
+    strategy::Strategy,
+    tournament::{Format, Outcome, Tournament},
+    ultimate::Ultimate,
+    DisplayOptions, Game, GameOutcome, MatchResult, Piece, Player, Server, DEFAULT_IDLE_TIMEOUT,
+    MAX_DIM,
+};
+use rustyline::error::ReadlineError;
+use save::DEFAULT_RATINGS_PATH;
+use seats::{parse_strategy, StrategyArg};
 
+fn main() {
     let args = Cli::parse();
-    let mut game = Game::new(args.dim, args.players);
+    let plain = base_display_options(&args).plain;
+    let name = |player: Player| {
+        if plain {
+            format!("{player:#}")
+        } else {
+            player.to_string()
+        }
+    };
+
+    match &args.command {
+        Some(Mode::Server {
+            dim,
+            players,
+            address,
+            idle_timeout,
+        }) => {
+            let game = dim
+                .zip(*players)
+                .map(|(dim, players)| Game::new(dim, players));
+            let idle_timeout = Duration::from_secs(*idle_timeout);
+            if let Err(e) = serve(address, game, idle_timeout) {
+                eprintln!("Could not host games: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(feature = "web")]
+        Some(Mode::WebServer {
+            address,
+            idle_timeout,
+        }) => {
+            if let Err(e) = serve_web(address, Duration::from_secs(*idle_timeout)) {
+                eprintln!("Could not host games: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Mode::Client {
+            address,
+            list,
+            game,
+            dim,
+            players,
+            rejoin,
+            spectate,
+        }) => {
+            let result = if *list {
+                client::list(address)
+            } else {
+                let seat = match (spectate, rejoin, dim.zip(*players), game) {
+                    (Some(game), ..) => Seat::Spectate(*game),
+                    (_, Some((game, token)), ..) => Seat::Rejoin {
+                        game: *game,
+                        token: *token,
+                    },
+                    (_, _, Some((dim, players)), _) => Seat::New { dim, players },
+                    (.., Some(game)) => Seat::Game(*game),
+                    _ => Seat::First,
+                };
+                client::run(address, seat, base_display_options(&args))
+            };
+            if let Err(e) = result {
+                eprintln!("Could not play on {address}: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Mode::Match {
+            first,
+            second,
+            dim,
+            games,
+            movetime,
+        }) => {
+            let movetime = Duration::from_millis(*movetime);
+            let options = base_display_options(&args);
+            let result = first.start("First", movetime, &options).and_then(|mut a| {
+                let mut b = second.start("Second", movetime, &options)?;
+                play_match(a.as_mut(), b.as_mut(), *dim, *games)
+            });
+            match result {
+                Ok(result) => print_match_result(first, second, &result),
+                Err(e) => {
+                    eprintln!("Could not play the match: {e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Mode::Tournament {
+            path,
+            participants,
+            dims,
+            rounds,
+            movetime,
+        }) => {
+            let format = match rounds {
+                Some(rounds) => Format::Swiss(*rounds),
+                None => Format::RoundRobin,
+            };
+            let result = run_tournament(
+                path,
+                participants,
+                dims,
+                format,
+                Duration::from_millis(*movetime),
+                &base_display_options(&args),
+                &args.ratings,
+            );
+            if let Err(e) = result {
+                eprintln!("Could not run the tournament: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Mode::Ultimate { dim, players }) => {
+            play_ultimate(*dim, *players, &args);
+            return;
+        }
+        Some(Mode::Replay { path, game }) => {
+            if let Err(e) = replay(path, *game, &args) {
+                eprintln!("Could not replay {path}: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Mode::Ratings) => {
+            match save::load_ratings(&args.ratings) {
+                Ok(ratings) => print_ratings(&ratings),
+                Err(e) => {
+                    eprintln!("Could not load {}: {e}", args.ratings);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        None => (),
+    }
+
+    clear_screen(plain);
+
+    let (mut game, clock) = match &args.load {
+        Some(path) => save::load(path).unwrap_or_else(|e| {
+            eprintln!("Could not load {path}: {e}");
+            std::process::exit(1);
+        }),
+        None => (Game::new(args.dim.unwrap(), args.players.unwrap()), None),
+    };
+    // Saved games keep their own clocks
+    let mut clock = clock.or_else(|| {
+        args.clock
+            .map(|control| Clock::new(control, game.players()))
+    });
+
+    if let Some(path) = &args.export_svg {
+        if let Err(e) = std::fs::write(path, game.to_svg(&display_options(&args, &game))) {
+            eprintln!("Could not export {path}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &args.export_replay {
+        if let Err(e) = export_replay(&game, path, &display_options(&args, &game)) {
+            eprintln!("Could not export {path}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "tui")]
+    if args.tui {
+        let options = display_options(&args, &game);
+        tui::run(game, options).expect("Could not run the terminal interface");
+        return;
+    }
+
+    let mut input = Input::new().expect("Could not open the terminal for input");
+
+    // People play at the prompt below, with all of its commands, so only the
+    // other seats need a strategy
+    let movetime = Duration::from_millis(args.movetime);
+    let engines = args
+        .engines
+        .iter()
+        .map(|(symbol, command)| (*symbol, StrategyArg::Engine(command.clone())));
+    let mut seats: HashMap<Player, Box<dyn Strategy>> = HashMap::new();
+    for (symbol, strategy) in args.seats.iter().cloned().chain(engines) {
+        let Some(player) = game.players().iter().find(|p| p.symbol() == symbol) else {
+            eprintln!("There's no player {symbol} in this game");
+            std::process::exit(1);
+        };
+        if let StrategyArg::Human = strategy {
+            seats.remove(player);
+            continue;
+        }
+        let started = strategy
+            .start(
+                &symbol.to_string(),
+                movetime,
+                &display_options(&args, &game),
+            )
+            .and_then(|mut seat| {
+                seat.new_game(&game)?;
+                Ok(seat)
+            });
+        match started {
+            Ok(seat) => seats.insert(*player, seat),
+            Err(e) => {
+                eprintln!("Could not start `{strategy}`: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+
+    // Only games where everyone has a name are rated
+    for (symbol, _) in &args.names {
+        if !game.players().iter().any(|p| p.symbol() == *symbol) {
+            eprintln!("There's no player {symbol} in this game");
+            std::process::exit(1);
+        }
+    }
+    let names: Option<Vec<_>> = game
+        .players()
+        .iter()
+        .map(|player| {
+            let (_, name) = args.names.iter().find(|(s, _)| *s == player.symbol())?;
+            Some(name.as_str())
+        })
+        .collect();
 
     let mut top_message: String = format!(
-        "Starting a {}-dimensional tic-tac-toe game with {} players",
-        args.dim, args.players
+        "Starting a {}-dimensional tic-tac-toe game with {} players (type `help` for commands)",
+        game.dim(),
+        game.players().len()
     );
     let mut last_error: Option<String> = None;
+    let mut view = View::default();
+    let mut time_outcome = None;
     loop {
-        // Clear the screen
-        print!("\x1B[2J\x1B[1;1H");
+        clear_screen(plain);
+        if let Some(clock) = &mut clock {
+            clock.start(game.current_player());
+        }
 
         // Print the top message
         println!("{}", top_message);
 
-        // Print the board
-        println!("{}\n", game.display(args.hide_padding));
+        // Print the board (or the part of it being viewed)
+        let options = display_options(&args, &game);
+        let board = match &view {
+            View::Full => game.display_with(&options).to_string(),
+            View::Slice(axes) => {
+                let slice = match View::fixed(axes, game.dim()) {
+                    Some(fixed) => game
+                        .display_slice(&fixed, &options)
+                        .map_err(|e| e.to_string()),
+                    None => Err(format!("There are only {} axes", game.dim())),
+                };
+                match slice {
+                    Ok(slice) => slice.to_string(),
+                    Err(e) => {
+                        // Go back to showing everything
+                        view = View::Full;
+                        last_error = Some(format!("Could not view slice: {e}"));
+                        continue;
+                    }
+                }
+            }
+            View::Occupied => game.display_occupied_slices(&options).to_string(),
+        };
+        match &clock {
+            Some(clock) if plain => println!("{}\n", beside(&board, &format!("{clock:#}"))),
+            Some(clock) => println!("{}\n", beside(&board, &clock.to_string())),
+            None => println!("{board}\n"),
+        }
 
         // Get the next player's move
         let player = game.current_player();
-        println!(
-            "{}: \x1b[1m{}\x1b[0m",
-            player,
-            if let Some(last_error) = last_error {
-                last_error
-            } else {
-                "".to_string()
+        let error = last_error.take().unwrap_or_default();
+        if plain {
+            println!("{}: {}", name(player), error);
+        } else {
+            println!("{}: \x1b[1m{}\x1b[0m", player, error);
+        }
+        let coords = if let Some(seat) = seats.get_mut(&player) {
+            if let Some(clock) = &clock {
+                seat.set_time_limit(clock.time_for_move(player));
+            }
+            match seat.choose(&game, player) {
+                Ok(coords) => game.map_player_coords(coords),
+                Err(e) => {
+                    println!("{} couldn't move: {e}", name(player));
+                    break;
+                }
+            }
+        } else {
+            let line = match input.read_line() {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+                Err(e) => panic!("Could not read input: {e}"),
+            };
+            match line.parse() {
+                Ok(Command::Move(coords)) => coords,
+                Ok(Command::Undo) => {
+                    last_error = None;
+                    match game.undo() {
+                        Some((coords, player)) => {
+                            top_message = format!(
+                                "Undid move: {} at {:?}",
+                                name(player),
+                                game.map_player_coords(coords)
+                            )
+                        }
+                        None => last_error = Some("No moves to undo".to_string()),
+                    }
+                    continue;
+                }
+                Ok(Command::Save(path)) => {
+                    let path = path.as_deref().unwrap_or(input::DEFAULT_SAVE_PATH);
+                    match save::save(&game, clock.as_ref(), path) {
+                        Ok(_) => {
+                            last_error = None;
+                            top_message = format!("Saved game to {path}");
+                        }
+                        Err(e) => last_error = Some(format!("Could not save game: {e}")),
+                    }
+                    continue;
+                }
+                Ok(Command::Hint) => {
+                    last_error = None;
+                    top_message = match game.hint(player) {
+                        Some(coords) => format!("Hint: try {:?}", game.map_player_coords(coords)),
+                        None => "No moves left".to_string(),
+                    };
+                    continue;
+                }
+                Ok(Command::View(new_view)) => {
+                    last_error = None;
+                    view = new_view;
+                    continue;
+                }
+                Ok(Command::Quit) => break,
+                Ok(Command::Help) => {
+                    last_error = None;
+                    top_message = input::HELP.to_string();
+                    continue;
+                }
+                Err(e) => {
+                    last_error = Some(e.to_string());
+                    continue;
+                }
             }
-        );
-        let Ok(coords) = get_player_input() else {
-            last_error = Some("Invalid input".to_string());
-            continue;
         };
 
-        top_message = format!("Last move: {} at {:?}", player, coords);
+        top_message = format!("Last move: {} at {:?}", name(player), coords);
 
         // Adjust input
-        let coords = if args.dim % 2 != 0 {
-            map_player_input(coords)
-        } else {
-            coords
-        };
+        let coords = game.map_player_coords(coords);
+
+        if let Some(loser) = clock.as_ref().and_then(Clock::out_of_time) {
+            clear_screen(plain);
+            println!("{}", game.display_with(&display_options(&args, &game)));
+            println!("{} ran out of time!", name(loser));
+
+            // With two players, the other one wins. With more, there's no
+            // fair way to pick a winner, so the game just ends.
+            if let [a, b] = game.players() {
+                let winner = if *a == loser { *b } else { *a };
+                println!("{} wins!", name(winner));
+                let outcome = GameOutcome::Win(winner);
+                time_outcome = Some(outcome);
+                if let Some(names) = &names {
+                    update_ratings(&args.ratings, game.players(), names, outcome);
+                }
+            } else {
+                println!("The game ends without a result.");
+            }
+            break;
+        }
 
         // Check if the player's move is valid
         match game.place_piece(Piece::new(player), coords) {
             Ok(_) => {
                 last_error = None;
+                if let Some(clock) = &mut clock {
+                    clock.stop();
+                }
 
                 // Check if the game is over
-                if game.check_win(player) {
-                    // Clear the screen
-                    print!("\x1B[2J\x1B[1;1H");
+                if let Some(outcome) = game.outcome() {
+                    clear_screen(plain);
 
                     // Print the board
-                    println!("{}", game.display(args.hide_padding));
-                    println!("\x1b[1m{}\x1b[1m wins!\x1b[0m", player);
+                    println!("{}", game.display_with(&display_options(&args, &game)));
+                    match outcome {
+                        GameOutcome::Win(_) if plain => println!("{} wins!", name(player)),
+                        GameOutcome::Win(_) => println!("\x1b[1m{}\x1b[1m wins!\x1b[0m", player),
+                        GameOutcome::Draw => println!("It's a draw!"),
+                    }
+                    if let Some(names) = &names {
+                        update_ratings(&args.ratings, game.players(), names, outcome);
+                    }
 
                     // Exit
                     break;
                 }
             }
+            // Strategies would just make the same move again
+            Err(e) if seats.contains_key(&player) => {
+                println!("{} made an illegal move: {e}", name(player));
+                break;
+            }
             Err(e) => {
                 last_error = Some(e.to_string());
                 continue;
@@ -76,60 +449,726 @@ fn main() {
         };
     }
 
+    if let Some(path) = &args.record {
+        let mut record = Record::new(&game);
+        record.names = game
+            .players()
+            .iter()
+            .map(|player| {
+                let (_, name) = args.names.iter().find(|(s, _)| *s == player.symbol())?;
+                Some(name.clone())
+            })
+            .collect();
+        record.date = Some(save::today());
+        record.result = record.result.or(time_outcome);
+        match save::archive(&record, path) {
+            Ok(()) => println!("Added the game to {path}"),
+            Err(e) => eprintln!("Could not add the game to {path}: {e}"),
+        }
+    }
+
     println!("Game over. Goodbye!")
 }
 
-fn get_player_input() -> std::io::Result<VecDeque<usize>> {
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
+/// Plays a game of ultimate tic-tac-toe, with everyone at this keyboard.
+fn play_ultimate(dim: usize, players: u32, args: &Cli) {
+    let plain = base_display_options(args).plain;
+    let mut input = Input::new().expect("Could not open the terminal for input");
+    let mut ultimate = Ultimate::new(dim, players);
+    let mut top_message = format!(
+        "Starting a {dim}-dimensional game of ultimate tic-tac-toe with {players} players \
+         (type `help` for commands)"
+    );
+    let mut error = String::new();
+    loop {
+        clear_screen(plain);
+        println!("{top_message}");
+        let options = DisplayOptions {
+            highlights: ultimate.highlights(),
+            ..base_display_options(args)
+        };
+        println!("{}\n", ultimate.display_with(&options));
+
+        match ultimate.outcome() {
+            Some(GameOutcome::Win(player)) => {
+                println!("{player:#} wins!");
+                break;
+            }
+            Some(GameOutcome::Draw) => {
+                println!("It's a draw!");
+                break;
+            }
+            None => (),
+        }
+
+        let player = ultimate.current_player();
+        let board = match ultimate.sent_to() {
+            Some(board) => format!("play on board {board:?}"),
+            None => "play on any board".to_string(),
+        };
+        if plain {
+            println!("{player:#} ({board}): {error}");
+        } else {
+            println!("{player} ({board}): \x1b[1m{error}\x1b[0m");
+        }
+        error.clear();
 
-    let input = input.split_whitespace().map(|x| x.parse());
+        let line = match input.read_line() {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => panic!("Could not read input: {e}"),
+        };
+        match line.parse() {
+            Ok(Command::Move(mut board)) if board.len() == dim * 2 => {
+                let cell = board.split_off(dim);
+                match ultimate.make_move(board.clone(), cell.clone()) {
+                    Ok(()) => top_message = format!("Last move: {player:#} at {board:?} {cell:?}"),
+                    Err(e) => error = e.to_string(),
+                }
+            }
+            Ok(Command::Move(_)) => {
+                error = format!("Enter {dim} coordinates for the board and {dim} for the cell")
+            }
+            Ok(Command::Undo) => match ultimate.unmake_move() {
+                Some((board, cell, player)) => {
+                    top_message = format!("Undid move: {player:#} at {board:?} {cell:?}")
+                }
+                None => error = "No moves to undo".to_string(),
+            },
+            Ok(Command::Quit) => break,
+            Ok(Command::Help) => top_message = input::ULTIMATE_HELP.to_string(),
+            Ok(_) => error = "That doesn't work in ultimate games".to_string(),
+            Err(e) => error = e.to_string(),
+        }
+    }
+
+    println!("Game over. Goodbye!")
+}
+
+fn clear_screen(plain: bool) {
+    if !plain {
+        print!("\x1B[2J\x1B[1;1H");
+    }
+}
+
+/// Hosts games for clients to create and join, starting with `game` if
+/// there is one. This runs until the server is stopped.
+fn serve(address: &str, game: Option<Game>, idle_timeout: Duration) -> std::io::Result<()> {
+    let mut server = Server::bind(address, idle_timeout)?;
+    if let Some(game) = game {
+        server.host(game);
+    }
+    println!("Hosting games on {}", server.local_addr()?);
+    server.run()
+}
+
+#[cfg(feature = "web")]
+fn serve_web(address: &str, idle_timeout: Duration) -> std::io::Result<()> {
+    let server = nd_tic_tac_toe::web::WebServer::bind(address, idle_timeout)?;
+    println!("Hosting games on ws://{}", server.local_addr()?);
+    server.run()
+}
+
+fn print_match_result(first: &StrategyArg, second: &StrategyArg, result: &MatchResult) {
+    let (low, high) = result.elo_interval();
+    println!("{first} vs {second}, {} games", result.games());
+    println!(
+        "Wins: {}, draws: {}, losses: {} (for {first})",
+        result.wins, result.draws, result.losses
+    );
+    println!("Average game length: {:.1} moves", result.average_length());
+    // There's no telling how much better a strategy that won every game is
+    let games = result.games();
+    if result.wins == games || result.losses == games {
+        let (winner, bound) = if result.wins == games {
+            (first, "at least")
+        } else {
+            (second, "at most")
+        };
+        println!(
+            "Elo difference: {bound} {:+.1} ({winner} won every game)",
+            result.elo_difference()
+        );
+        return;
+    }
+    println!(
+        "Elo difference: {:+.1} (95% confidence interval: {low:+.1} to {high:+.1})",
+        result.elo_difference()
+    );
+}
 
-    if !input.clone().all(|r| r.is_ok()) {
+/// Plays the rest of the tournament saved at `path`, or a new one between
+/// `participants` if there are any, saving it after every game and updating
+/// the ratings at `ratings`.
+fn run_tournament(
+    path: &str,
+    participants: &[(String, StrategyArg)],
+    dims: &[usize],
+    format: Format,
+    movetime: Duration,
+    options: &DisplayOptions,
+    ratings: &str,
+) -> std::io::Result<()> {
+    let invalid = |message| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            message,
+        ))
+    };
+    let (mut tournament, participants) = if participants.is_empty() {
+        save::load_tournament(path)?
+    } else if std::path::Path::new(path).exists() {
         return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Invalid input",
+            std::io::ErrorKind::AlreadyExists,
+            format!("{path} already exists (leave out the participants to resume it)"),
         ));
+    } else if participants.len() < 2 {
+        return invalid("Tournaments need at least 2 participants");
+    } else if !participants.iter().map(|(name, _)| name).all_unique() {
+        return invalid("Participants need different names");
+    } else if !dims.iter().all(|dim| (1..=MAX_DIM).contains(dim)) {
+        return invalid(&format!("Games need 1 to {MAX_DIM} dimensions"));
+    } else {
+        let tournament = Tournament::new(participants.len(), dims.to_vec(), format);
+        save::save_tournament(&tournament, participants, path)?;
+        (tournament, participants.to_vec())
+    };
+
+    // Started when they first play
+    let mut strategies: Vec<_> = participants.iter().map(|_| None).collect();
+    while let Some(index) = tournament.next_game() {
+        let pairing = tournament.pairings()[index].clone();
+        let (first, second) = (pairing.first, pairing.second);
+        println!(
+            "Round {}: {} vs {} with {} dimensions",
+            pairing.round, participants[first].0, participants[second].0, pairing.dim
+        );
+
+        for participant in [first, second] {
+            if strategies[participant].is_none() {
+                let (name, strategy) = &participants[participant];
+                strategies[participant] = Some(strategy.start(name, movetime, options)?);
+            }
+        }
+        let mut a = strategies[first].take().unwrap();
+        let mut b = strategies[second].take().unwrap();
+        let mut game = Game::new(pairing.dim, 2);
+        let outcome = play_game(&mut game, &mut [a.as_mut(), b.as_mut()]);
+        strategies[first] = Some(a);
+        strategies[second] = Some(b);
+
+        let outcome = match outcome? {
+            GameOutcome::Win(player) if player == game.players()[0] => Outcome::Win(first),
+            GameOutcome::Win(_) => Outcome::Win(second),
+            GameOutcome::Draw => Outcome::Draw,
+        };
+        match outcome {
+            Outcome::Win(winner) => println!("{} wins!", participants[winner].0),
+            Outcome::Draw => println!("It's a draw!"),
+        }
+        tournament.record(index, outcome);
+        save::save_tournament(&tournament, &participants, path)?;
+        let names = [participants[first].0.as_str(), &participants[second].0];
+        if let Some(outcome) = game.outcome() {
+            update_ratings(ratings, game.players(), &names, outcome);
+        }
+    }
+
+    print_standings(&tournament, &participants);
+    Ok(())
+}
+
+fn print_standings(tournament: &Tournament, participants: &[(String, StrategyArg)]) {
+    let width = participants
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default()
+        .max("Name".len());
+    println!("\n     {:width$}  Points   W   D   L  Byes", "Name");
+    for (rank, standing) in tournament.standings().iter().enumerate() {
+        println!(
+            "{:>3}. {:width$}  {:>6.1} {:>3} {:>3} {:>3} {:>5}",
+            rank + 1,
+            participants[standing.participant].0,
+            standing.points,
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.byes
+        );
+    }
+}
+
+/// Rates a game between `players` (named `names`), in the ratings file at
+/// `path`.
+fn update_ratings(path: &str, players: &[Player], names: &[&str], outcome: GameOutcome) {
+    let result = save::load_ratings(path).and_then(|mut ratings| {
+        ratings.record_outcome(players, names, outcome);
+        save::save_ratings(&ratings, path)
+    });
+    if let Err(e) = result {
+        eprintln!("Could not update the ratings in {path}: {e}");
+    }
+}
+
+fn print_ratings(ratings: &Ratings) {
+    let leaderboard = ratings.leaderboard();
+    if leaderboard.is_empty() {
+        println!("Nobody has been rated yet");
+        return;
+    }
+
+    let width = leaderboard
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default()
+        .max("Name".len());
+    println!("     {:width$}  Rating  Games   W   D   L", "Name");
+    for (rank, (name, rating)) in leaderboard.iter().enumerate() {
+        println!(
+            "{:>3}. {name:width$}  {:>6.0}  {:>5} {:>3} {:>3} {:>3}",
+            rank + 1,
+            rating.rating,
+            rating.games(),
+            rating.wins,
+            rating.draws,
+            rating.losses
+        );
+    }
+}
+
+/// Shows the moves of the `number`th game in the archive at `path` one at a
+/// time, waiting for Enter between them (when there's someone to press it).
+fn replay(path: &str, number: usize, args: &Cli) -> std::io::Result<()> {
+    let records = save::load_archive(path)?;
+    let Some(record) = number.checked_sub(1).and_then(|i| records.get(i)) else {
+        let message = format!("There are only {} games in the archive", records.len());
+        return Err(std::io::Error::other(message));
+    };
+    let full = record
+        .game()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+    let plain = base_display_options(args).plain;
+    let mut input = std::io::stdin()
+        .is_terminal()
+        .then(Input::new)
+        .transpose()
+        .map_err(std::io::Error::other)?;
+
+    let players = full
+        .players()
+        .iter()
+        .zip(&record.names)
+        .map(|(player, name)| format!("{} ({})", name.as_deref().unwrap_or("?"), player.symbol()))
+        .join(" vs ");
+    let date = record.date.as_deref().unwrap_or("unknown date");
+
+    let mut game = Game::new(full.dim(), full.players().len() as u32);
+    let moves: Vec<_> = full.moves().collect();
+    for i in 0..=moves.len() {
+        clear_screen(plain);
+        println!("{players}, {date}");
+        match i.checked_sub(1).map(|i| &moves[i]) {
+            Some((coords, player)) => println!(
+                "Move {i} of {}: {player:#} at {:?}",
+                moves.len(),
+                game.map_player_coords(coords.clone())
+            ),
+            None => println!("Move 0 of {}", moves.len()),
+        }
+        println!("{}\n", game.display_with(&display_options(args, &game)));
+
+        let Some((coords, player)) = moves.get(i) else {
+            break;
+        };
+        if let Some(input) = &mut input {
+            println!("Press Enter for the next move, or type `quit`");
+            match input.read_line() {
+                Ok(line) if line.trim() != "quit" => (),
+                _ => return Ok(()),
+            }
+        }
+        game.place_piece(Piece::new(*player), coords.clone())
+            .expect("The record was checked");
     }
 
-    let input: VecDeque<usize> = input.map(|r| r.unwrap()).collect();
-    Ok(input)
+    match record.result {
+        Some(GameOutcome::Win(player)) => println!("{player:#} won"),
+        Some(GameOutcome::Draw) => println!("It was a draw"),
+        None => println!("The game didn't finish"),
+    }
+    Ok(())
+}
+
+/// Puts the lines of `side` to the right of the lines of `board`.
+fn beside(board: &str, side: &str) -> String {
+    let width = board.lines().map(visible_width).max().unwrap_or_default();
+    board
+        .lines()
+        .zip_longest(side.lines())
+        .map(|lines| match lines {
+            EitherOrBoth::Both(line, side) => {
+                let padding = width - visible_width(line);
+                format!("{line}{:padding$}   {side}", "")
+            }
+            EitherOrBoth::Left(line) => line.to_string(),
+            EitherOrBoth::Right(side) => format!("{:width$}   {side}", ""),
+        })
+        .join("\n")
 }
 
-fn map_player_input(input: VecDeque<usize>) -> VecDeque<usize> {
-    let chunks = input
-        .into_iter()
-        // Turns `x1 y2 x2` into `x2 y2 x1`
-        .rev()
-        // Then `(x2 y2) (x1)`
-        .chunks(2);
+/// The number of characters in `line`, leaving out color codes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut escape = false;
+    for c in line.chars() {
+        match c {
+            '\x1b' => escape = true,
+            'm' if escape => escape = false,
+            _ if escape => (),
+            _ => width += 1,
+        }
+    }
+    width
+}
 
-    let mut chunks_vec = Vec::new();
-    for chunk in &chunks {
-        chunks_vec.push(chunk);
+/// Writes an animated SVG if `path` ends in `.svg`, otherwise a directory of
+/// numbered SVG frames.
+fn export_replay(game: &Game, path: &str, options: &DisplayOptions) -> std::io::Result<()> {
+    if path.ends_with(".svg") {
+        return std::fs::write(path, game.replay_svg(options, 1.0));
     }
 
-    chunks_vec
-        .into_iter()
-        // Then `(x1) (x2 y2)`
-        .rev()
-        // Then `x1 x2 y2`
-        .flatten()
-        .collect()
+    let frames = game.replay_svg_frames(options);
+    let digits = frames.len().to_string().len();
+    std::fs::create_dir_all(path)?;
+    for (i, frame) in frames.iter().enumerate() {
+        std::fs::write(format!("{path}/{:0digits$}.svg", i + 1), frame)?;
+    }
+    Ok(())
+}
+
+fn display_options(args: &Cli, game: &Game) -> DisplayOptions {
+    DisplayOptions {
+        highlights: game.highlights(),
+        ..base_display_options(args)
+    }
+}
+
+/// The options from the command line, without any highlights. Output is plain
+/// when it's asked for, or when it isn't going to a terminal.
+fn base_display_options(args: &Cli) -> DisplayOptions {
+    DisplayOptions {
+        hide_padding: args.hide_padding,
+        labels: args.labels,
+        plain: args.no_color || !std::io::stdout().is_terminal(),
+        compact: args.compact,
+        ..Default::default()
+    }
 }
 
 /// Start an n-dimensional tic-tac-toe game.
 #[derive(Parser)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Mode>,
+
     /// The number of dimensions in the game
-    #[arg(short = 'd', long = "dim")]
-    dim: usize,
+    #[arg(short = 'd', long = "dim", required_unless_present = "load")]
+    dim: Option<usize>,
 
     /// The number of players in the game
-    #[arg(short = 'p', long = "players")]
-    players: u32,
+    #[arg(short = 'p', long = "players", required_unless_present = "load")]
+    players: Option<u32>,
+
+    /// Resume a game saved with the `save` command
+    #[arg(short = 'l', long = "load", conflicts_with_all = ["dim", "players"])]
+    load: Option<String>,
+
+    /// Write the board to an SVG file and exit, instead of playing
+    #[arg(long = "export-svg", value_name = "PATH")]
+    export_svg: Option<String>,
+
+    /// Write a replay of the game (usually loaded with `--load`) and exit.
+    /// Paths ending in `.svg` get an animated SVG, anything else is a
+    /// directory of numbered SVG frames.
+    #[arg(long = "export-replay", value_name = "PATH")]
+    export_replay: Option<String>,
+
+    /// Choose who plays as a player, like `O=center` or `F=script:moves.txt`.
+    /// The strategies are the same as for `match`, plus `network:ADDRESS`
+    /// for an engine on another machine. Players are `human` by default.
+    #[arg(long = "seat", value_name = "SYMBOL=STRATEGY", value_parser = parse_seat)]
+    seats: Vec<(char, StrategyArg)>,
+
+    /// Let an external engine play as a player, like `O=./my-engine --fast`
+    /// (short for `--seat O=engine:./my-engine --fast`). See
+    /// `nd_tic_tac_toe::engine` for the protocol engines speak.
+    #[arg(short = 'e', long = "engine", value_name = "SYMBOL=COMMAND", value_parser = parse_engine)]
+    engines: Vec<(char, String)>,
+
+    /// How long engines get to choose each move
+    #[arg(long = "movetime", value_name = "MILLISECONDS", default_value_t = DEFAULT_MOVETIME.as_millis() as u64)]
+    movetime: u64,
+
+    /// Add the game to the archive at this path when it's over, to replay it
+    /// later with `replay`
+    #[arg(long = "record", value_name = "PATH")]
+    record: Option<String>,
+
+    /// Name a player, like `X=alice`. Games where every player has a name are
+    /// rated when they're over.
+    #[arg(short = 'n', long = "name", value_name = "SYMBOL=NAME", value_parser = parse_name)]
+    names: Vec<(char, String)>,
+
+    /// Play with a clock: `MINUTES+SECONDS` for a total time per player plus
+    /// an increment after each move (like `5+2`), or `SECONDS/move` for a
+    /// fixed time per move. Running out of time loses two-player games, and
+    /// ends games with more players without a result.
+    #[arg(long = "clock", value_name = "TIME", value_parser = parse_time_control)]
+    clock: Option<TimeControl>,
+
+    /// Use the full-screen terminal interface
+    #[cfg(feature = "tui")]
+    #[arg(short = 't', long = "tui", conflicts_with_all = ["clock", "names", "seats", "engines", "record"])]
+    tui: bool,
 
     // Whether or not to pad each piece with spaces
-    #[arg(short = 's', long = "hide-padding")]
+    #[arg(short = 's', long = "hide-padding", global = true)]
     hide_padding: bool,
+
+    /// Label the axes with coordinates. The labels are lettered in the order
+    /// coordinates are entered in.
+    #[arg(short = 'L', long = "labels", global = true)]
+    labels: bool,
+
+    /// Leave out the empty 2D boards, showing a map of which ones have pieces
+    /// on them instead
+    #[arg(short = 'c', long = "compact", global = true)]
+    compact: bool,
+
+    /// The file ratings are kept in
+    #[arg(long = "ratings", value_name = "PATH", default_value = DEFAULT_RATINGS_PATH, global = true)]
+    ratings: String,
+
+    /// Print plain text without colors or clearing the screen. This is the
+    /// default when the output isn't a terminal.
+    #[arg(long = "no-color", global = true)]
+    no_color: bool,
+}
+
+/// Ways to play other than sharing one terminal
+#[derive(Subcommand)]
+enum Mode {
+    /// Host games for players on other machines to join with `client`
+    Server {
+        /// Start with a game with this many dimensions (clients can create
+        /// more)
+        #[arg(short = 'd', long = "dim", requires = "players", value_parser = dim_parser())]
+        dim: Option<usize>,
+
+        /// The number of players in the starting game
+        #[arg(short = 'p', long = "players", requires = "dim", value_parser = clap::value_parser!(u32).range(1..=3))]
+        players: Option<u32>,
+
+        /// The address to listen on
+        #[arg(short = 'a', long = "address", default_value = "0.0.0.0:7878")]
+        address: String,
+
+        /// Close games after this many seconds without any moves
+        #[arg(long = "idle-timeout", value_name = "SECONDS", default_value_t = DEFAULT_IDLE_TIMEOUT.as_secs())]
+        idle_timeout: u64,
+    },
+    /// Host games over WebSocket, with JSON messages, for browser front-ends
+    #[cfg(feature = "web")]
+    WebServer {
+        /// The address to listen on
+        #[arg(short = 'a', long = "address", default_value = "0.0.0.0:8080")]
+        address: String,
+
+        /// Close games after this many seconds without any moves
+        #[arg(long = "idle-timeout", value_name = "SECONDS", default_value_t = DEFAULT_IDLE_TIMEOUT.as_secs())]
+        idle_timeout: u64,
+    },
+    /// Play two strategies against each other, taking turns going first, and
+    /// compare how they did
+    Match {
+        /// The first strategy: `human`, `ai` for the built-in AI, `random`,
+        /// `greedy` (win or block if possible, otherwise random), `center`
+        /// (win or block, otherwise the cell on the most lines),
+        /// `engine:COMMAND` for an external engine, `network:ADDRESS` for an
+        /// engine on another machine, or `script:PATH` for moves from a file
+        /// (one per line)
+        #[arg(value_parser = parse_strategy)]
+        first: StrategyArg,
+
+        /// The second strategy
+        #[arg(value_parser = parse_strategy)]
+        second: StrategyArg,
+
+        /// The number of dimensions in each game
+        #[arg(short = 'd', long = "dim", default_value_t = 2, value_parser = dim_parser())]
+        dim: usize,
+
+        /// The number of games to play
+        #[arg(short = 'n', long = "games", default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        games: u32,
+
+        /// How long engines get to choose each move
+        #[arg(long = "movetime", value_name = "MILLISECONDS", default_value_t = DEFAULT_MOVETIME.as_millis() as u64)]
+        movetime: u64,
+    },
+    /// Run a round-robin or Swiss tournament, saving it after every game so
+    /// it can be resumed
+    Tournament {
+        /// Where the tournament is saved. Leave out the participants to resume
+        /// the tournament saved here.
+        path: String,
+
+        /// Someone taking part, with a name and how they play (any of the
+        /// strategies `match` takes), like `alice=human` or `bot=greedy`
+        #[arg(short = 'P', long = "participant", value_name = "NAME=STRATEGY", value_parser = parse_participant)]
+        participants: Vec<(String, StrategyArg)>,
+
+        /// The number of dimensions in each game, with a game of each size for
+        /// every pairing
+        #[arg(short = 'd', long = "dims", value_delimiter = ',', default_value = "2")]
+        dims: Vec<usize>,
+
+        /// Play a Swiss tournament with this many rounds, instead of a round
+        /// robin
+        #[arg(long = "swiss", value_name = "ROUNDS", value_parser = clap::value_parser!(u32).range(1..))]
+        rounds: Option<u32>,
+
+        /// How long engines get to choose each move
+        #[arg(long = "movetime", value_name = "MILLISECONDS", default_value_t = DEFAULT_MOVETIME.as_millis() as u64)]
+        movetime: u64,
+    },
+    /// Show everyone's ratings, best first
+    Ratings,
+    /// Play ultimate tic-tac-toe, where each cell of the board is a board of
+    /// its own
+    Ultimate {
+        /// The number of dimensions of the big board and each small one
+        #[arg(short = 'd', long = "dim", default_value_t = 2, value_parser = dim_parser())]
+        dim: usize,
+
+        /// The number of players
+        #[arg(short = 'p', long = "players", default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..=3))]
+        players: u32,
+    },
+    /// Step through a game from an archive made with `--record`
+    Replay {
+        /// The archive
+        path: String,
+
+        /// Which game in the archive to replay, from 1
+        #[arg(short = 'g', long = "game", default_value_t = 1)]
+        game: usize,
+    },
+    /// Join a game hosted with `server`
+    Client {
+        /// The address of the server
+        #[arg(default_value = "127.0.0.1:7878")]
+        address: String,
+
+        /// List the games on the server instead of joining one
+        #[arg(long = "list", conflicts_with_all = ["game", "dim", "rejoin", "spectate"])]
+        list: bool,
+
+        /// The game to join, instead of the first one with a free seat
+        #[arg(short = 'g', long = "game")]
+        game: Option<u32>,
+
+        /// Create a game with this many dimensions and join it
+        #[arg(
+            short = 'd',
+            long = "dim",
+            requires = "players",
+            conflicts_with = "game"
+        )]
+        dim: Option<usize>,
+
+        /// The number of players in the created game
+        #[arg(short = 'p', long = "players", requires = "dim")]
+        players: Option<u32>,
+
+        /// Take back a seat after being disconnected, with the game and token
+        /// shown when joining it
+        #[arg(long = "rejoin", value_name = "GAME:TOKEN", value_parser = parse_rejoin, conflicts_with_all = ["game", "dim"])]
+        rejoin: Option<(u32, u64)>,
+
+        /// Watch a game without playing in it
+        #[arg(long = "spectate", value_name = "GAME", conflicts_with_all = ["game", "dim", "rejoin"])]
+        spectate: Option<u32>,
+    },
+}
+
+fn parse_rejoin(s: &str) -> Result<(u32, u64), String> {
+    let invalid = || "expected the game and token, like `1:3fa2c0`".to_string();
+    let (game, token) = s.split_once(':').ok_or_else(invalid)?;
+    let game = game.parse().map_err(|_| invalid())?;
+    let token = u64::from_str_radix(token, 16).map_err(|_| invalid())?;
+    Ok((game, token))
+}
+
+fn parse_engine(s: &str) -> Result<(char, String), String> {
+    let invalid = || "expected a player and a command, like `O=./my-engine`".to_string();
+    let (symbol, command) = s.split_once('=').ok_or_else(invalid)?;
+    let symbol = symbol.parse().map_err(|_| invalid())?;
+    Ok((symbol, command.to_string()))
+}
+
+fn parse_seat(s: &str) -> Result<(char, StrategyArg), String> {
+    let invalid = || "expected a player and a strategy, like `O=center`".to_string();
+    let (symbol, strategy) = s.split_once('=').ok_or_else(invalid)?;
+    let symbol = symbol.parse().map_err(|_| invalid())?;
+    Ok((symbol, parse_strategy(strategy)?))
+}
+
+/// Allows the same numbers of dimensions as games created on a server.
+fn dim_parser() -> clap::builder::RangedU64ValueParser<usize> {
+    clap::builder::RangedU64ValueParser::new().range(1..=MAX_DIM as u64)
+}
+
+fn parse_name(s: &str) -> Result<(char, String), String> {
+    let invalid = || "expected a player and a name, like `X=alice`".to_string();
+    let (symbol, name) = s.split_once('=').ok_or_else(invalid)?;
+    let symbol = symbol.parse().map_err(|_| invalid())?;
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err("names can't be empty or contain spaces".to_string());
+    }
+    Ok((symbol, name.to_string()))
+}
+
+fn parse_participant(s: &str) -> Result<(String, StrategyArg), String> {
+    let invalid = || "expected a name and a strategy, like `alice=human`".to_string();
+    let (name, strategy) = s.split_once('=').ok_or_else(invalid)?;
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err("names can't be empty or contain spaces".to_string());
+    }
+    Ok((name.to_string(), parse_strategy(strategy)?))
+}
+
+fn parse_time_control(s: &str) -> Result<TimeControl, String> {
+    let invalid = || "expected a time control like `5+2` or `30/move`".to_string();
+    let seconds = |x: &str| {
+        x.parse::<f64>()
+            .ok()
+            .and_then(|x| Duration::try_from_secs_f64(x).ok())
+            .ok_or_else(invalid)
+    };
+    if let Some(time) = s.strip_suffix("/move") {
+        return Ok(TimeControl::PerMove(seconds(time)?));
+    }
+    let (total, increment) = s.split_once('+').unwrap_or((s, "0"));
+    Ok(TimeControl::Increment {
+        total: seconds(total)?
+            .checked_mul(60)
+            .ok_or_else(|| "that's more time than a clock can hold".to_string())?,
+        increment: seconds(increment)?,
+    })
 }
@@ -0,0 +1,118 @@
+//! Playing strategies against each other.
+
+use std::io;
+
+use super::{strategy::Strategy, Game, GameOutcome, Piece};
+
+/// How the first strategy in a match did against the second.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The number of moves played in all the games together
+    pub moves: usize,
+}
+
+impl MatchResult {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn average_length(&self) -> f64 {
+        self.moves as f64 / self.games() as f64
+    }
+
+    /// The fraction of the points the first strategy got, counting draws as
+    /// half a point.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// How much higher the first strategy's Elo rating is than the second's,
+    /// going by the score. If either strategy won every game, half a point of
+    /// it is counted as the other strategy's, so that this isn't infinite.
+    pub fn elo_difference(&self) -> f64 {
+        elo_difference(self.bounded_score())
+    }
+
+    /// The range the Elo difference is in with 95% confidence.
+    pub fn elo_interval(&self) -> (f64, f64) {
+        let games = self.games() as f64;
+        let score = self.bounded_score();
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games;
+        let margin = 1.96 * (variance / games).sqrt();
+        (
+            elo_difference((score - margin).max(0.5 / games)),
+            elo_difference((score + margin).min(1.0 - 0.5 / games)),
+        )
+    }
+
+    /// The score, with half a point less for a strategy that won every game
+    fn bounded_score(&self) -> f64 {
+        let half_game = 0.5 / self.games() as f64;
+        self.score().clamp(half_game, 1.0 - half_game)
+    }
+}
+
+fn elo_difference(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+/// Plays out `game`, with `seats[i]` making the moves for the `i`th player.
+///
+/// # Errors
+///
+/// Fails if a strategy does, or makes an illegal move.
+pub fn play_game(game: &mut Game, seats: &mut [&mut dyn Strategy]) -> io::Result<GameOutcome> {
+    for strategy in seats.iter_mut() {
+        strategy.new_game(game)?;
+    }
+
+    loop {
+        if let Some(outcome) = game.outcome() {
+            return Ok(outcome);
+        }
+
+        let player = game.current_player();
+        let seat = game.players().iter().position(|p| *p == player).unwrap();
+        let coords = seats[seat].choose(game, player)?;
+        game.place_piece(Piece::new(player), coords).map_err(|e| {
+            let message = format!("{player:#} made an illegal move: {e}");
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+    }
+}
+
+/// Plays `games` two-player games with `dim` dimensions between `first` and
+/// `second`, taking turns going first.
+pub fn play_match(
+    first: &mut dyn Strategy,
+    second: &mut dyn Strategy,
+    dim: usize,
+    games: u32,
+) -> io::Result<MatchResult> {
+    let mut result = MatchResult::default();
+    for i in 0..games {
+        let mut game = Game::new(dim, 2);
+        let first_moves = i % 2 == 0;
+        let outcome = if first_moves {
+            play_game(&mut game, &mut [&mut *first, &mut *second])?
+        } else {
+            play_game(&mut game, &mut [&mut *second, &mut *first])?
+        };
+
+        result.moves += game.moves().count();
+        match outcome {
+            GameOutcome::Draw => result.draws += 1,
+            GameOutcome::Win(player) if (player == game.players()[0]) == first_moves => {
+                result.wins += 1
+            }
+            GameOutcome::Win(_) => result.losses += 1,
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,192 @@
+//! A short way of writing down a position, to paste into bug reports and
+//! tests.
+//!
+//! The notation has five fields separated by spaces: the number of
+//! dimensions, the width, the number of players, the symbol of the player
+//! whose turn it is, and the cells. The cells are listed in the order the
+//! board stores them in, with `.` for an empty cell and runs of the same
+//! cell written once after their length. For example, `2 3 2 X X3.O4.` is a
+//! 2D game with X in one corner and O in the middle, and X to move.
+
+use itertools::Itertools;
+
+use super::{Game, NotationError, Piece, Player, MAX_DIM};
+
+impl Game {
+    /// Reads a position written with [`to_notation`](Self::to_notation).
+    ///
+    /// The order the pieces were played in isn't part of the notation, so
+    /// they're played in turns in the order the board stores them in, except
+    /// that each player's last piece is one on a line they completed (if
+    /// any), so that wins still count.
+    pub fn from_notation(notation: &str) -> Result<Self, NotationError> {
+        let [dim, width, players, side, cells] = notation.split_whitespace().collect_vec()[..]
+        else {
+            return Err(NotationError::Malformed);
+        };
+        let dim: usize = dim.parse().map_err(|_| NotationError::Malformed)?;
+        let width: usize = width.parse().map_err(|_| NotationError::Malformed)?;
+        let players: u32 = players.parse().map_err(|_| NotationError::Malformed)?;
+        if dim == 0 || players == 0 || Player::try_from(players - 1).is_err() {
+            return Err(NotationError::Malformed);
+        }
+        if dim > MAX_DIM {
+            return Err(NotationError::TooManyDimensions);
+        }
+        if width != dim + 1 {
+            return Err(NotationError::UnsupportedWidth);
+        }
+        let total = width
+            .checked_pow(dim as u32)
+            .ok_or(NotationError::TooManyDimensions)?;
+
+        let mut game = Game::new(dim, players);
+        let player = |symbol: char| {
+            game.players()
+                .iter()
+                .position(|p| p.symbol() == symbol)
+                .ok_or(NotationError::Malformed)
+        };
+        let side = match side.chars().collect_vec()[..] {
+            [symbol] => player(symbol)?,
+            _ => return Err(NotationError::Malformed),
+        };
+
+        // Where each player's pieces are, by index into the flattened board
+        let mut pieces = vec![Vec::new(); players as usize];
+        let mut index: usize = 0;
+        let mut run = String::new();
+        for c in cells.chars() {
+            if c.is_ascii_digit() {
+                run.push(c);
+                continue;
+            }
+            let length = match run.as_str() {
+                "" => 1,
+                run => run.parse().map_err(|_| NotationError::Malformed)?,
+            };
+            run.clear();
+            if length == 0 {
+                return Err(NotationError::Malformed);
+            }
+            let end = index
+                .checked_add(length)
+                .filter(|end| *end <= total)
+                .ok_or(NotationError::WrongCellCount)?;
+            if c != '.' {
+                pieces[player(c)?].extend(index..end);
+            }
+            index = end;
+        }
+        if !run.is_empty() {
+            return Err(NotationError::Malformed);
+        }
+        if index != total {
+            return Err(NotationError::WrongCellCount);
+        }
+
+        // The players before the one to move have had one more turn
+        let turns = pieces[side].len();
+        for (i, pieces) in pieces.iter().enumerate() {
+            if pieces.len() != turns + usize::from(i < side) {
+                return Err(NotationError::ImpossibleTurn);
+            }
+        }
+        // Only a player's last piece is checked for wins, so it has to be on
+        // a line they completed. The lines are found by looking at each one
+        // on the board, and then checked the same way a game would.
+        for (i, pieces) in pieces.iter_mut().enumerate() {
+            let player = game.players()[i];
+            let mut owned = vec![false; total];
+            for index in pieces.iter() {
+                owned[*index] = true;
+            }
+            let last = lines(dim, width)
+                .filter(|line| line.iter().all(|index| owned[*index]))
+                .map(|line| line[0])
+                .find(|last| {
+                    for index in pieces.iter().filter(|index| *index != last) {
+                        game.place_piece(Piece::new(player), game.get_coords(*index))
+                            .unwrap();
+                    }
+                    game.place_piece(Piece::new(player), game.get_coords(*last))
+                        .unwrap();
+                    let wins = game.check_win(player);
+                    while game.unmake_move().is_some() {}
+                    wins
+                });
+            if let Some(last) = last {
+                pieces.retain(|index| *index != last);
+                pieces.push(last);
+            }
+        }
+
+        for turn in 0..=turns {
+            for (i, pieces) in pieces.iter().enumerate() {
+                if let Some(index) = pieces.get(turn) {
+                    let piece = Piece::new(game.players()[i]);
+                    game.place_piece(piece, game.get_coords(*index))
+                        .expect("Each cell is only listed once");
+                }
+            }
+        }
+        Ok(game)
+    }
+
+    /// Writes down the position, in a form [`from_notation`](Self::from_notation)
+    /// can read back.
+    pub fn to_notation(&self) -> String {
+        let cells = self
+            .board
+            .flatten()
+            .into_iter()
+            .map(|piece| piece.player.map_or('.', |player| player.symbol()))
+            .dedup_with_count()
+            .map(|(length, c)| match length {
+                1 => c.to_string(),
+                _ => format!("{length}{c}"),
+            })
+            .join("");
+        format!(
+            "{} {} {} {} {cells}",
+            self.dim(),
+            self.width(),
+            self.players().len(),
+            self.current_player().symbol(),
+        )
+    }
+}
+
+/// Every line on a board, as indexes into the flattened board. Along each
+/// axis, a line either stays at one coordinate or goes from one side to the
+/// other, and each line is only listed going forwards along the first axis
+/// it moves along.
+fn lines(dim: usize, width: usize) -> impl Iterator<Item = Vec<usize>> {
+    // How each axis changes along the line: -1, 0 or 1
+    let directions = (0..dim)
+        .map(|_| [-1isize, 0, 1])
+        .multi_cartesian_product()
+        .filter(|direction| direction.iter().find(|d| **d != 0) == Some(&1));
+    directions.flat_map(move |direction| {
+        // Where the line can start on each axis
+        let starts = direction
+            .iter()
+            .map(|d| match d {
+                0 => (0..width).collect_vec(),
+                1 => vec![0],
+                _ => vec![width - 1],
+            })
+            .multi_cartesian_product();
+        starts
+            .map(|start| {
+                (0..width as isize)
+                    .map(|t| {
+                        start.iter().zip(&direction).fold(0, |index, (x, d)| {
+                            index * width + (*x as isize + t * d) as usize
+                        })
+                    })
+                    .collect_vec()
+            })
+            .collect_vec()
+    })
+}
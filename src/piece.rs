@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use super::Player;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Piece {
     pub player: Option<Player>,
 }
@@ -19,8 +19,16 @@ impl Piece {
     }
 }
 
+/// The alternate form (`{:#}`) is plain ASCII, without any colors.
 impl Display for Piece {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return match self.player {
+                Some(player) => write!(f, "{}", player.symbol()),
+                None => f.write_str("."),
+            };
+        }
+
         // If there isn't a player, create a temporary "player" that will be
         // used only for displaying. (it simplifies the code)
         let player = self.player.unwrap_or_else(|| Player::new('•'));
@@ -37,8 +37,13 @@ impl TryFrom<u32> for Player {
     }
 }
 
+/// The alternate form (`{:#}`) is plain text, without any colors.
 impl Display for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return write!(f, "Player {}", self.symbol());
+        }
+
         f.write_str(format!("Player {}", self.with_color()).as_str())
     }
 }
@@ -0,0 +1,271 @@
+//! The line-based protocol spoken between a [`Server`](crate::Server) and its
+//! clients.
+//!
+//! Each message is a single line of words separated by spaces, starting with
+//! the kind of message. Coordinates are in the order players enter them in
+//! (see [`Game::map_player_coords`]), and tokens are in hexadecimal.
+//!
+//! From a client to the server:
+//!
+//! - `create <dim> <players>`: hosts a new game.
+//! - `list`: asks for the games being hosted.
+//! - `join [<game>]`: takes the next free seat in a game, or in the first
+//!   game with one.
+//! - `rejoin <game> <token>`: takes back a seat after being disconnected.
+//! - `spectate <game>`: watches a game without playing in it. Spectators get
+//!   every `state` and `result` the players do, but can't make moves.
+//! - `move <x> <y> ...`: places a piece for the client's player.
+//!
+//! From the server to a client:
+//!
+//! - `created <game>`: the game was created. Creating a game doesn't join it.
+//! - `games <game>,<dim>,<players>,<free seats>,<spectators> ...`: the games
+//!   being hosted.
+//! - `join <symbol> <dim> <players> <game> <token>`: the client is playing as
+//!   `symbol` in a game with `dim` dimensions and `players` players, and can
+//!   rejoin with `token` if it gets disconnected.
+//! - `spectate <game> <dim> <players>`: the client is watching the game.
+//! - `state <move> ...`: every move played so far, oldest first, with the
+//!   coordinates of each separated by commas (e.g. `state 0,0 1,2`). Players
+//!   take turns in order, so this is enough to rebuild the game. It's sent to
+//!   everyone in the game when someone joins, and after every move.
+//! - `error <message>`: the last message couldn't be handled, or something
+//!   happened in the game (like a player being disconnected).
+//! - `result <symbol>` or `result draw`: the game is over.
+
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+use super::{board::Idx, Game, GameInfo, GameOutcome, Piece, PlacePieceError, Player};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    Create { dim: usize, players: u32 },
+    List,
+    Join(Option<u32>),
+    Rejoin { game: u32, token: u64 },
+    Spectate(u32),
+    Move(Idx),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    Created(u32),
+    Games(Vec<GameInfo>),
+    Join {
+        player: Player,
+        dim: usize,
+        players: u32,
+        game: u32,
+        token: u64,
+    },
+    Spectate {
+        game: u32,
+        dim: usize,
+        players: u32,
+    },
+    State(Vec<Idx>),
+    Error(String),
+    Result(GameOutcome),
+}
+
+impl ServerMessage {
+    /// The moves played in `game` so far.
+    pub fn state(game: &Game) -> Self {
+        Self::State(
+            game.moves()
+                .map(|(coords, _)| game.map_player_coords(coords))
+                .collect(),
+        )
+    }
+}
+
+/// Rebuilds a game from the moves in a [`ServerMessage::State`].
+pub fn replay_state(dim: usize, players: u32, moves: &[Idx]) -> Result<Game, PlacePieceError> {
+    let mut game = Game::new(dim, players);
+    for coords in moves {
+        let player = game.current_player();
+        game.place_piece(Piece::new(player), game.map_player_coords(coords.clone()))?;
+    }
+    Ok(game)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseMessageError {
+    Empty,
+    UnknownMessage(String),
+    InvalidArguments(String),
+}
+
+impl Display for ParseMessageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseMessageError::Empty => f.write_str("Empty message"),
+            ParseMessageError::UnknownMessage(kind) => write!(f, "Unknown message `{kind}`"),
+            ParseMessageError::InvalidArguments(kind) => {
+                write!(f, "Invalid arguments to `{kind}`")
+            }
+        }
+    }
+}
+
+impl Display for ClientMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientMessage::Create { dim, players } => write!(f, "create {dim} {players}"),
+            ClientMessage::List => f.write_str("list"),
+            ClientMessage::Join(None) => f.write_str("join"),
+            ClientMessage::Join(Some(game)) => write!(f, "join {game}"),
+            ClientMessage::Rejoin { game, token } => write!(f, "rejoin {game} {token:x}"),
+            ClientMessage::Spectate(game) => write!(f, "spectate {game}"),
+            ClientMessage::Move(coords) => write!(f, "move {}", coords.iter().join(" ")),
+        }
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = ParseMessageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let kind = words.next().ok_or(ParseMessageError::Empty)?;
+        let invalid = || ParseMessageError::InvalidArguments(kind.to_string());
+        let words: Vec<_> = words.collect();
+        let number = |word: &str| word.parse().map_err(|_| invalid());
+
+        match (kind, words.as_slice()) {
+            ("create", [dim, players]) => Ok(ClientMessage::Create {
+                dim: dim.parse().map_err(|_| invalid())?,
+                players: number(players)?,
+            }),
+            ("list", []) => Ok(ClientMessage::List),
+            ("join", []) => Ok(ClientMessage::Join(None)),
+            ("join", [game]) => Ok(ClientMessage::Join(Some(number(game)?))),
+            ("rejoin", [game, token]) => Ok(ClientMessage::Rejoin {
+                game: number(game)?,
+                token: u64::from_str_radix(token, 16).map_err(|_| invalid())?,
+            }),
+            ("spectate", [game]) => Ok(ClientMessage::Spectate(number(game)?)),
+            ("move", coords) if !coords.is_empty() => coords
+                .iter()
+                .map(|x| x.parse())
+                .collect::<Result<_, _>>()
+                .map(ClientMessage::Move)
+                .map_err(|_| invalid()),
+            ("create" | "list" | "join" | "rejoin" | "spectate" | "move", _) => Err(invalid()),
+            _ => Err(ParseMessageError::UnknownMessage(kind.to_string())),
+        }
+    }
+}
+
+impl Display for ServerMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerMessage::Created(game) => write!(f, "created {game}"),
+            ServerMessage::Games(games) => {
+                f.write_str("games")?;
+                for info in games {
+                    let GameInfo {
+                        id,
+                        dim,
+                        players,
+                        open_seats,
+                        spectators,
+                    } = info;
+                    write!(f, " {id},{dim},{players},{open_seats},{spectators}")?;
+                }
+                Ok(())
+            }
+            ServerMessage::Join {
+                player,
+                dim,
+                players,
+                game,
+                token,
+            } => write!(
+                f,
+                "join {} {dim} {players} {game} {token:x}",
+                player.symbol()
+            ),
+            ServerMessage::Spectate { game, dim, players } => {
+                write!(f, "spectate {game} {dim} {players}")
+            }
+            ServerMessage::State(moves) => {
+                f.write_str("state")?;
+                for coords in moves {
+                    write!(f, " {}", coords.iter().join(","))?;
+                }
+                Ok(())
+            }
+            ServerMessage::Error(message) => write!(f, "error {message}"),
+            ServerMessage::Result(GameOutcome::Win(player)) => {
+                write!(f, "result {}", player.symbol())
+            }
+            ServerMessage::Result(GameOutcome::Draw) => f.write_str("result draw"),
+        }
+    }
+}
+
+impl FromStr for ServerMessage {
+    type Err = ParseMessageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, rest) = s.split_once(' ').unwrap_or((s, ""));
+        let invalid = || ParseMessageError::InvalidArguments(kind.to_string());
+        let symbol = |word: &str| word.chars().exactly_one().ok().map(Player::new);
+        let words: Vec<_> = rest.split_whitespace().collect();
+
+        match (kind, words.as_slice()) {
+            ("", _) => Err(ParseMessageError::Empty),
+            ("created", [game]) => game
+                .parse()
+                .map(ServerMessage::Created)
+                .map_err(|_| invalid()),
+            ("games", games) => games
+                .iter()
+                .map(|info| {
+                    let (id, dim, players, open_seats, spectators) = info
+                        .split(',')
+                        .map(|x| x.parse::<usize>())
+                        .collect_tuple()?;
+                    Some(GameInfo {
+                        id: id.ok()? as u32,
+                        dim: dim.ok()?,
+                        players: players.ok()? as u32,
+                        open_seats: open_seats.ok()?,
+                        spectators: spectators.ok()?,
+                    })
+                })
+                .collect::<Option<_>>()
+                .map(ServerMessage::Games)
+                .ok_or_else(invalid),
+            ("join", [player, dim, players, game, token]) => Ok(ServerMessage::Join {
+                player: symbol(player).ok_or_else(invalid)?,
+                dim: dim.parse().map_err(|_| invalid())?,
+                players: players.parse().map_err(|_| invalid())?,
+                game: game.parse().map_err(|_| invalid())?,
+                token: u64::from_str_radix(token, 16).map_err(|_| invalid())?,
+            }),
+            ("spectate", [game, dim, players]) => Ok(ServerMessage::Spectate {
+                game: game.parse().map_err(|_| invalid())?,
+                dim: dim.parse().map_err(|_| invalid())?,
+                players: players.parse().map_err(|_| invalid())?,
+            }),
+            ("state", moves) => moves
+                .iter()
+                .map(|coords| coords.split(',').map(|x| x.parse()).collect())
+                .collect::<Result<_, _>>()
+                .map(ServerMessage::State)
+                .map_err(|_| invalid()),
+            ("error", _) => Ok(ServerMessage::Error(rest.to_string())),
+            ("result", ["draw"]) => Ok(ServerMessage::Result(GameOutcome::Draw)),
+            ("result", [player]) => symbol(player)
+                .map(|player| ServerMessage::Result(GameOutcome::Win(player)))
+                .ok_or_else(invalid),
+            ("created" | "join" | "spectate" | "result", _) => Err(invalid()),
+            _ => Err(ParseMessageError::UnknownMessage(kind.to_string())),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Elo ratings for people (or strategies) who play each other regularly.
+//!
+//! Games with more than two players are rated as if each pair of players had
+//! played a game of their own: the winner beat everyone else, and everyone
+//! else drew with each other.
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use super::{Game, GameOutcome, Player};
+
+/// The rating new players start with
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The most a rating can change by after a two-player game. It's shared
+/// between the pairs in games with more players.
+const K: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        }
+    }
+}
+
+impl Rating {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// Everyone's ratings, by name.
+#[derive(Debug, Clone, Default)]
+pub struct Ratings(BTreeMap<String, Rating>);
+
+impl Ratings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rating> {
+        self.0.get(name)
+    }
+
+    /// Everyone, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Rating)> {
+        self.0.iter().map(|(name, rating)| (name.as_str(), rating))
+    }
+
+    /// Everyone, with the highest rating first.
+    pub fn leaderboard(&self) -> Vec<(&str, &Rating)> {
+        let mut leaderboard: Vec<_> = self.iter().collect();
+        leaderboard.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+        leaderboard
+    }
+
+    /// Updates the ratings of the people who played `game`, who are named in
+    /// turn order. Games that aren't over yet don't count. Returns the
+    /// outcome if the game was rated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't a name for each player.
+    pub fn record(&mut self, game: &Game, names: &[&str]) -> Option<GameOutcome> {
+        let outcome = game.outcome()?;
+        self.record_outcome(game.players(), names, outcome);
+        Some(outcome)
+    }
+
+    /// Updates the ratings of `players`, who are named in the same order, for
+    /// a game that ended with `outcome` (which might not be the game's own,
+    /// like when someone runs out of time).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't a name for each player.
+    pub fn record_outcome(&mut self, players: &[Player], names: &[&str], outcome: GameOutcome) {
+        assert_eq!(names.len(), players.len(), "A name for each player");
+        let won = |player: usize| outcome == GameOutcome::Win(players[player]);
+        let k = K / (names.len() - 1) as f64;
+
+        // Work everything out from the ratings before the game
+        let before: Vec<_> = names
+            .iter()
+            .map(|name| self.get(name).copied().unwrap_or_default().rating)
+            .collect();
+        let mut changes = vec![0.0; names.len()];
+        for (a, b) in (0..names.len()).tuple_combinations() {
+            if names[a] == names[b] {
+                continue;
+            }
+            let expected = 1.0 / (1.0 + 10f64.powf((before[b] - before[a]) / 400.0));
+            // Two players who both lost (or both drew) drew with each other
+            let actual = match (won(a), won(b)) {
+                (true, false) => 1.0,
+                (false, true) => 0.0,
+                _ => 0.5,
+            };
+            changes[a] += k * (actual - expected);
+            changes[b] -= k * (actual - expected);
+        }
+
+        for (player, name) in names.iter().enumerate() {
+            let rating = self.0.entry(name.to_string()).or_default();
+            rating.rating += changes[player];
+            match outcome {
+                GameOutcome::Draw => rating.draws += 1,
+                _ if won(player) => rating.wins += 1,
+                _ => rating.losses += 1,
+            }
+        }
+    }
+}
+
+impl FromIterator<(String, Rating)> for Ratings {
+    fn from_iter<T: IntoIterator<Item = (String, Rating)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
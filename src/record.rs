@@ -0,0 +1,282 @@
+//! Game records, for archiving games and replaying them later (like PGN for
+//! chess).
+//!
+//! A record starts with tags, one per line, followed by a blank line and the
+//! moves:
+//!
+//! ```text
+//! [Date "2024.03.09"]
+//! [Players "alice bob"]
+//! [Dim "2"]
+//! [Width "3"]
+//! [Result "draw"]
+//!
+//! 1. 1,1 0,0 2. 0,2 2,0 3. 1,0 1,2 4. 2,1 0,1 5. 2,2 draw
+//! ```
+//!
+//! The `Players` tag has each player's name in turn order, with `?` for
+//! players without one, and the `Result` tag is the winner's symbol, `draw`,
+//! or `*` for games that didn't finish. The `Date` and `Result` tags are
+//! optional, and tags that aren't known are skipped.
+//!
+//! Moves are numbered by round (with a move for each player) and written
+//! with their coordinates in the order players enter them in, separated by
+//! commas. The result comes again at the end. Files can hold any number of
+//! records, one after the other.
+
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+use super::{Game, GameOutcome, Player, MAX_DIM};
+
+/// How long lines of moves get before they're wrapped
+const LINE_WIDTH: usize = 79;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Each player's name in turn order, if they have one
+    pub names: Vec<Option<String>>,
+    /// When the game was played, like `2024.03.09`
+    pub date: Option<String>,
+    pub dim: usize,
+    pub width: usize,
+    /// How the game ended, or `None` if it didn't
+    pub result: Option<GameOutcome>,
+    /// Each move, with its coordinates in the order players enter them in
+    pub moves: Vec<VecDeque<usize>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecordError {
+    /// A line in the tags wasn't a tag like `[Dim "2"]`.
+    InvalidTag(String),
+    MissingTag(&'static str),
+    /// The value of the tag with this name couldn't be read.
+    InvalidValue(&'static str),
+    /// Something in the moves wasn't a move number, move or result.
+    InvalidMove(String),
+    /// The width wasn't one more than the number of dimensions, which is the
+    /// only width games can have.
+    UnsupportedWidth,
+    /// There were more than [`MAX_DIM`] dimensions.
+    TooManyDimensions,
+    /// The move with this number (from 1) couldn't be made.
+    IllegalMove(usize),
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordError::InvalidTag(line) => write!(f, "Invalid tag: {line}"),
+            RecordError::MissingTag(name) => write!(f, "Missing the {name} tag"),
+            RecordError::InvalidValue(name) => write!(f, "Invalid value for the {name} tag"),
+            RecordError::InvalidMove(token) => write!(f, "Invalid move: {token}"),
+            RecordError::UnsupportedWidth => {
+                f.write_str("The width must be one more than the number of dimensions")
+            }
+            RecordError::TooManyDimensions => {
+                write!(f, "Games can have at most {MAX_DIM} dimensions")
+            }
+            RecordError::IllegalMove(n) => write!(f, "Move {n} can't be made"),
+        }
+    }
+}
+
+impl Record {
+    /// A record of `game` so far, without any names or date.
+    pub fn new(game: &Game) -> Self {
+        Self {
+            names: vec![None; game.players().len()],
+            date: None,
+            dim: game.dim(),
+            width: game.width(),
+            result: game.outcome(),
+            moves: game
+                .moves()
+                .map(|(coords, _)| game.map_player_coords(coords))
+                .collect(),
+        }
+    }
+
+    /// Reads every record in `s`, like a file of archived games.
+    pub fn parse_all(s: &str) -> Result<Vec<Self>, RecordError> {
+        // Each record starts at the first tag after some moves
+        let mut records = vec![String::new()];
+        let mut in_moves = false;
+        for line in s.lines() {
+            if line.trim_start().starts_with('[') {
+                if in_moves {
+                    records.push(String::new());
+                }
+                in_moves = false;
+            } else if !line.trim().is_empty() {
+                in_moves = true;
+            }
+            let record = records.last_mut().unwrap();
+            record.push_str(line);
+            record.push('\n');
+        }
+        records
+            .iter()
+            .filter(|record| !record.trim().is_empty())
+            .map(|record| record.parse())
+            .collect()
+    }
+
+    /// Plays the game out again, from the start to the last move.
+    pub fn game(&self) -> Result<Game, RecordError> {
+        let players = self.names.len() as u32;
+        if self.dim == 0 {
+            return Err(RecordError::InvalidValue("Dim"));
+        }
+        if self.dim > MAX_DIM {
+            return Err(RecordError::TooManyDimensions);
+        }
+        if players == 0 || Player::try_from(players - 1).is_err() {
+            return Err(RecordError::InvalidValue("Players"));
+        }
+        if self.width != self.dim + 1 {
+            return Err(RecordError::UnsupportedWidth);
+        }
+
+        let mut game = Game::new(self.dim, players);
+        for (i, coords) in self.moves.iter().enumerate() {
+            game.make_move(game.map_player_coords(coords.clone()))
+                .map_err(|_| RecordError::IllegalMove(i + 1))?;
+        }
+        Ok(game)
+    }
+}
+
+impl FromStr for Record {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim);
+        let mut tags = Vec::new();
+        for line in lines.by_ref().skip_while(|line| line.is_empty()) {
+            if line.is_empty() {
+                break;
+            }
+            tags.push(parse_tag(line).ok_or_else(|| RecordError::InvalidTag(line.to_string()))?);
+        }
+        let tag = |name: &'static str| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let required = |name| tag(name).ok_or(RecordError::MissingTag(name));
+        let number = |name| {
+            required(name)?
+                .parse()
+                .map_err(|_| RecordError::InvalidValue(name))
+        };
+
+        let names: Vec<_> = required("Players")?
+            .split_whitespace()
+            .map(|name| (name != "?").then(|| name.to_string()))
+            .collect();
+        let players: Vec<_> = (0..names.len() as u32)
+            .map(Player::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|_| RecordError::InvalidValue("Players"))?;
+        let result = match tag("Result") {
+            Some(result) => {
+                parse_result(result, &players).ok_or(RecordError::InvalidValue("Result"))?
+            }
+            None => None,
+        };
+
+        let mut moves = Vec::new();
+        let mut tokens = lines.flat_map(str::split_whitespace).peekable();
+        while let Some(token) = tokens.next() {
+            if let Some(round) = token.strip_suffix('.') {
+                if round.parse::<usize>().is_err() {
+                    return Err(RecordError::InvalidMove(token.to_string()));
+                }
+            } else if let Ok(coords) = token.split(',').map(str::parse).collect() {
+                moves.push(coords);
+            } else if tokens.peek().is_none() && parse_result(token, &players) == Some(result) {
+                // The result at the end
+            } else {
+                return Err(RecordError::InvalidMove(token.to_string()));
+            }
+        }
+
+        Ok(Self {
+            names,
+            date: tag("Date").map(str::to_string),
+            dim: number("Dim")?,
+            width: number("Width")?,
+            result,
+            moves,
+        })
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self
+            .names
+            .iter()
+            .map(|name| name.as_deref().unwrap_or("?"))
+            .join(" ");
+        let result = match self.result {
+            Some(GameOutcome::Win(player)) => player.symbol().to_string(),
+            Some(GameOutcome::Draw) => "draw".to_string(),
+            None => "*".to_string(),
+        };
+        if let Some(date) = &self.date {
+            writeln!(f, "[Date \"{date}\"]")?;
+        }
+        writeln!(f, "[Players \"{names}\"]")?;
+        writeln!(f, "[Dim \"{}\"]", self.dim)?;
+        writeln!(f, "[Width \"{}\"]", self.width)?;
+        writeln!(f, "[Result \"{result}\"]")?;
+        writeln!(f)?;
+
+        let players = self.names.len().max(1);
+        let tokens = self
+            .moves
+            .iter()
+            .enumerate()
+            .flat_map(|(i, coords)| {
+                let round = (i % players == 0).then(|| format!("{}.", i / players + 1));
+                round.into_iter().chain([coords.iter().join(",")])
+            })
+            .chain([result]);
+        let mut width = 0;
+        for token in tokens {
+            if width > 0 && width + 1 + token.len() > LINE_WIDTH {
+                writeln!(f)?;
+                width = 0;
+            } else if width > 0 {
+                write!(f, " ")?;
+                width += 1;
+            }
+            write!(f, "{token}")?;
+            width += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// Reads a tag like `[Dim "2"]`.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.strip_prefix('[')?.strip_suffix(']')?.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.to_string()))
+}
+
+/// Reads a result like `X`, `draw` or `*` (which is `Some(None)`).
+fn parse_result(s: &str, players: &[Player]) -> Option<Option<GameOutcome>> {
+    match s {
+        "draw" => Some(Some(GameOutcome::Draw)),
+        "*" => Some(None),
+        _ => players
+            .iter()
+            .find(|player| player.symbol().to_string() == s)
+            .map(|player| Some(GameOutcome::Win(*player))),
+    }
+}
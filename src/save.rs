@@ -0,0 +1,314 @@
+//! Saving and loading games and tournaments.
+//!
+//! A save file starts with a header line `nd-tic-tac-toe <dim> <players>`,
+//! followed by one line per move with the (internal) coordinates of the piece
+//! separated by spaces, oldest first. Games with a clock have a line after the
+//! header with the time control and each player's time left, in milliseconds:
+//! `clock increment <total> <increment> <remaining> ...` or `clock per-move
+//! <time>`.
+//!
+//! A tournament file starts with a header line `nd-tic-tac-toe-tournament
+//! <format> <dims>`, where the format is `round-robin` or `swiss:<rounds>` and
+//! the board sizes are separated by commas. Then there's a line `participant
+//! <name> <strategy>` for each participant, and a line for each game played so
+//! far with who won it (`first` or `second`) or `draw`, oldest first. The
+//! games themselves are scheduled again when loading.
+//!
+//! A ratings file starts with a header line `nd-tic-tac-toe-ratings`,
+//! followed by a line `<name> <rating> <wins> <draws> <losses>` for each
+//! player.
+//!
+//! Archives of finished games hold game records (see
+//! `nd_tic_tac_toe::record`), one after the other.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use itertools::Itertools;
+use nd_tic_tac_toe::{
+    clock::{Clock, TimeControl},
+    ratings::{Rating, Ratings},
+    record::Record,
+    tournament::{Format, Outcome, Tournament},
+    Game, Piece, MAX_DIM,
+};
+
+use crate::seats::{self, StrategyArg};
+
+const HEADER: &str = "nd-tic-tac-toe";
+const TOURNAMENT_HEADER: &str = "nd-tic-tac-toe-tournament";
+const RATINGS_HEADER: &str = "nd-tic-tac-toe-ratings";
+
+pub const DEFAULT_RATINGS_PATH: &str = "nd-tic-tac-toe.ratings";
+
+pub fn save(game: &Game, clock: Option<&Clock>, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut contents = format!("{HEADER} {} {}\n", game.dim(), game.players().len());
+    match clock.map(|clock| (clock, clock.control())) {
+        Some((clock, TimeControl::Increment { total, increment })) => {
+            let remaining = game
+                .players()
+                .iter()
+                .map(|player| clock.remaining(*player).as_millis())
+                .join(" ");
+            contents.push_str(&format!(
+                "clock increment {} {} {remaining}\n",
+                total.as_millis(),
+                increment.as_millis()
+            ));
+        }
+        Some((_, TimeControl::PerMove(time))) => {
+            contents.push_str(&format!("clock per-move {}\n", time.as_millis()));
+        }
+        None => (),
+    }
+    for (coords, _) in game.moves() {
+        contents.push_str(&coords.iter().join(" "));
+        contents.push('\n');
+    }
+
+    fs::write(path, contents)
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<(Game, Option<Clock>)> {
+    let contents = fs::read_to_string(path)?;
+    let mut lines = contents.lines().peekable();
+
+    let header = lines.next().unwrap_or_default();
+    let Some((HEADER, dim, players)) = header.split_whitespace().collect_tuple() else {
+        return Err(invalid_data("Missing header"));
+    };
+    let dim = dim
+        .parse()
+        .ok()
+        .filter(|dim| (1..=MAX_DIM).contains(dim))
+        .ok_or_else(|| invalid_data("Invalid dimension"))?;
+    let players = players
+        .parse()
+        .ok()
+        .filter(|players| (1..=3).contains(players))
+        .ok_or_else(|| invalid_data("Invalid number of players"))?;
+
+    let mut game = Game::new(dim, players);
+    let clock = lines
+        .next_if(|line| line.starts_with("clock "))
+        .map(|line| load_clock(line, &game))
+        .transpose()?;
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let coords = line
+            .split_whitespace()
+            .map(|x| x.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid_data("Invalid coordinate"))?;
+
+        let player = game.current_player();
+        game.place_piece(Piece::new(player), coords)
+            .map_err(|e| invalid_data(&e.to_string()))?;
+    }
+
+    Ok((game, clock))
+}
+
+fn load_clock(line: &str, game: &Game) -> io::Result<Clock> {
+    let times: Vec<Duration> = line
+        .split_whitespace()
+        .skip(2)
+        .map(|x| x.parse().map(Duration::from_millis))
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid_data("Invalid time"))?;
+
+    match (line.split_whitespace().nth(1), times.as_slice()) {
+        (Some("increment"), [total, increment, remaining @ ..])
+            if remaining.len() == game.players().len() =>
+        {
+            let control = TimeControl::Increment {
+                total: *total,
+                increment: *increment,
+            };
+            let mut clock = Clock::new(control, game.players());
+            for (player, time) in game.players().iter().zip(remaining) {
+                clock.set_remaining(*player, *time);
+            }
+            Ok(clock)
+        }
+        (Some("per-move"), [time]) => Ok(Clock::new(TimeControl::PerMove(*time), game.players())),
+        _ => Err(invalid_data("Invalid clock")),
+    }
+}
+
+pub fn save_tournament(
+    tournament: &Tournament,
+    participants: &[(String, StrategyArg)],
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let format = match tournament.format() {
+        Format::RoundRobin => "round-robin".to_string(),
+        Format::Swiss(rounds) => format!("swiss:{rounds}"),
+    };
+    let dims = tournament.dims().iter().join(",");
+    let mut contents = format!("{TOURNAMENT_HEADER} {format} {dims}\n");
+    for (name, strategy) in participants {
+        contents.push_str(&format!("participant {name} {strategy}\n"));
+    }
+    for pairing in tournament.pairings() {
+        let result = match pairing.outcome {
+            Some(Outcome::Win(winner)) if winner == pairing.first => "first",
+            Some(Outcome::Win(_)) => "second",
+            Some(Outcome::Draw) => "draw",
+            None => continue,
+        };
+        contents.push_str(result);
+        contents.push('\n');
+    }
+
+    fs::write(path, contents)
+}
+
+pub fn load_tournament(
+    path: impl AsRef<Path>,
+) -> io::Result<(Tournament, Vec<(String, StrategyArg)>)> {
+    let contents = fs::read_to_string(path)?;
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+
+    let header = lines.next().unwrap_or_default();
+    let Some((TOURNAMENT_HEADER, format, dims)) = header.split_whitespace().collect_tuple() else {
+        return Err(invalid_data("Missing header"));
+    };
+    let format = match format.split_once(':') {
+        None if format == "round-robin" => Format::RoundRobin,
+        Some(("swiss", rounds)) => Format::Swiss(
+            rounds
+                .parse()
+                .map_err(|_| invalid_data("Invalid number of rounds"))?,
+        ),
+        _ => return Err(invalid_data("Invalid format")),
+    };
+    let dims: Vec<usize> = dims
+        .split(',')
+        .map(|dim| dim.parse().ok().filter(|dim| (1..=MAX_DIM).contains(dim)))
+        .collect::<Option<_>>()
+        .ok_or_else(|| invalid_data("Invalid dimension"))?;
+
+    let mut participants = Vec::new();
+    let mut lines = lines.peekable();
+    while let Some(participant) = lines.next_if(|line| line.starts_with("participant ")) {
+        let mut words = participant.splitn(3, ' ').skip(1);
+        let (Some(name), Some(strategy)) = (words.next(), words.next()) else {
+            return Err(invalid_data("Invalid participant"));
+        };
+        let strategy = seats::parse_strategy(strategy).map_err(|e| invalid_data(&e))?;
+        participants.push((name.to_string(), strategy));
+    }
+    if participants.len() < 2 {
+        return Err(invalid_data("Not enough participants"));
+    }
+    if !participants.iter().map(|(name, _)| name).all_unique() {
+        return Err(invalid_data("Participants with the same name"));
+    }
+
+    let mut tournament = Tournament::new(participants.len(), dims, format);
+    for line in lines {
+        let game = tournament
+            .next_game()
+            .ok_or_else(|| invalid_data("Too many results"))?;
+        let pairing = &tournament.pairings()[game];
+        let outcome = match line.trim() {
+            "first" => Outcome::Win(pairing.first),
+            "second" => Outcome::Win(pairing.second),
+            "draw" => Outcome::Draw,
+            _ => return Err(invalid_data("Invalid result")),
+        };
+        tournament.record(game, outcome);
+    }
+
+    Ok((tournament, participants))
+}
+
+pub fn save_ratings(ratings: &Ratings, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut contents = format!("{RATINGS_HEADER}\n");
+    for (name, rating) in ratings.iter() {
+        let Rating {
+            rating,
+            wins,
+            draws,
+            losses,
+        } = rating;
+        contents.push_str(&format!("{name} {rating} {wins} {draws} {losses}\n"));
+    }
+
+    fs::write(path, contents)
+}
+
+/// Loads the ratings at `path`, or no ratings if there's no file there yet.
+pub fn load_ratings(path: impl AsRef<Path>) -> io::Result<Ratings> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Ratings::new()),
+        Err(e) => return Err(e),
+    };
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    if lines.next() != Some(RATINGS_HEADER) {
+        return Err(invalid_data("Missing header"));
+    }
+
+    lines
+        .map(|line| {
+            let (name, rating, wins, draws, losses) = line
+                .split_whitespace()
+                .collect_tuple()
+                .ok_or_else(|| invalid_data("Invalid rating"))?;
+            let number = |x: &str| x.parse().map_err(|_| invalid_data("Invalid number"));
+            let rating = Rating {
+                rating: rating.parse().map_err(|_| invalid_data("Invalid rating"))?,
+                wins: number(wins)?,
+                draws: number(draws)?,
+                losses: number(losses)?,
+            };
+            Ok((name.to_string(), rating))
+        })
+        .collect()
+}
+
+/// Adds `record` to the end of the archive at `path`, creating it if it
+/// doesn't exist yet.
+pub fn archive(record: &Record, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    if file.metadata()?.len() > 0 {
+        writeln!(file)?;
+    }
+    write!(file, "{record}")
+}
+
+pub fn load_archive(path: impl AsRef<Path>) -> io::Result<Vec<Record>> {
+    Record::parse_all(&fs::read_to_string(path)?).map_err(|e| invalid_data(&e.to_string()))
+}
+
+/// Today's date (in UTC) for game records, like `2024.03.09`.
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    // From days since 1970-01-01 to a date, with years starting in March so
+    // that leap days come last (see http://howardhinnant.github.io/date_algorithms.html)
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
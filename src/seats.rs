@@ -0,0 +1,154 @@
+//! Who (or what) makes the moves for each player, in local games as well as
+//! matches and tournaments.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::Display,
+    fs,
+    io::{self, Stdout},
+    time::Duration,
+};
+
+use nd_tic_tac_toe::{
+    engine::Engine,
+    strategy::{self, Strategy},
+    DisplayOptions,
+};
+use rustyline::error::ReadlineError;
+
+use crate::input::Input;
+
+/// A strategy named on the command line
+#[derive(Clone)]
+pub enum StrategyArg {
+    Human,
+    Ai,
+    Random,
+    Greedy,
+    Center,
+    Engine(String),
+    /// An engine on another machine, at this address
+    Network(String),
+    /// Moves read from the file at this path
+    Script(String),
+}
+
+impl StrategyArg {
+    /// Gets the strategy ready to play. People are called `name`, and are
+    /// shown the board with `options`.
+    pub fn start(
+        &self,
+        name: &str,
+        movetime: Duration,
+        options: &DisplayOptions,
+    ) -> io::Result<Box<dyn Strategy>> {
+        Ok(match self {
+            StrategyArg::Human => Box::new(human(name, options.clone())),
+            StrategyArg::Ai => Box::new(strategy::Hint),
+            StrategyArg::Random => Box::new(strategy::Random::new()),
+            StrategyArg::Greedy => Box::new(strategy::Greedy::new()),
+            StrategyArg::Center => Box::new(strategy::Center::new()),
+            StrategyArg::Engine(command) => {
+                let mut engine = Engine::spawn(command)?;
+                engine.set_movetime(movetime);
+                Box::new(engine)
+            }
+            StrategyArg::Network(address) => {
+                let mut engine = Engine::connect(address.as_str())?;
+                engine.set_movetime(movetime);
+                Box::new(engine)
+            }
+            StrategyArg::Script(path) => Box::new(load_script(path)?),
+        })
+    }
+}
+
+impl Display for StrategyArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrategyArg::Human => f.write_str("human"),
+            StrategyArg::Ai => f.write_str("ai"),
+            StrategyArg::Random => f.write_str("random"),
+            StrategyArg::Greedy => f.write_str("greedy"),
+            StrategyArg::Center => f.write_str("center"),
+            StrategyArg::Engine(command) => write!(f, "engine:{command}"),
+            StrategyArg::Network(address) => write!(f, "network:{address}"),
+            StrategyArg::Script(path) => write!(f, "script:{path}"),
+        }
+    }
+}
+
+pub fn parse_strategy(s: &str) -> Result<StrategyArg, String> {
+    match s {
+        "human" => Ok(StrategyArg::Human),
+        "ai" => Ok(StrategyArg::Ai),
+        "random" => Ok(StrategyArg::Random),
+        "greedy" => Ok(StrategyArg::Greedy),
+        "center" => Ok(StrategyArg::Center),
+        _ => {
+            if let Some(command) = s.strip_prefix("engine:") {
+                Ok(StrategyArg::Engine(command.to_string()))
+            } else if let Some(address) = s.strip_prefix("network:") {
+                Ok(StrategyArg::Network(address.to_string()))
+            } else if let Some(path) = s.strip_prefix("script:") {
+                Ok(StrategyArg::Script(path.to_string()))
+            } else {
+                Err("expected `human`, `ai`, `random`, `greedy`, `center`, \
+                     `engine:COMMAND`, `network:ADDRESS` or `script:PATH`"
+                    .to_string())
+            }
+        }
+    }
+}
+
+/// Reads a script of moves, one per line, with the coordinates separated by
+/// spaces like when entering them. Blank lines are skipped.
+fn load_script(path: &str) -> io::Result<strategy::Scripted> {
+    let moves: Result<Vec<VecDeque<usize>>, _> = fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split_whitespace().map(|x| x.parse()).collect())
+        .collect();
+    let moves = moves.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(strategy::Scripted::new(moves))
+}
+
+thread_local! {
+    /// The line editor every human seat reads from, so they share its
+    /// history and take turns at the terminal
+    static INPUT: RefCell<Option<Input>> = const { RefCell::new(None) };
+}
+
+/// Someone at the keyboard, who's shown the board before each move like in a
+/// local game.
+fn human(name: &str, options: DisplayOptions) -> strategy::Human<strategy::Lines, Stdout> {
+    let read_line = || {
+        INPUT.with_borrow_mut(|input| {
+            let input = match input {
+                Some(input) => input,
+                None => input.insert(Input::new().map_err(io::Error::other)?),
+            };
+            match input.read_line() {
+                Ok(line) => Ok(Some(line)),
+                Err(ReadlineError::Interrupted) => Ok(Some("quit".to_string())),
+                Err(ReadlineError::Eof) => Ok(None),
+                Err(e) => Err(io::Error::other(e)),
+            }
+        })
+    };
+    let name = name.to_string();
+    strategy::Human::new(strategy::Lines::new(read_line), io::stdout()).with_prompt(
+        move |game, player| {
+            let board = game.display_with(&DisplayOptions {
+                highlights: game.highlights(),
+                ..options.clone()
+            });
+            if options.plain {
+                format!("{board}\n\n{name} ({player:#}):\n")
+            } else {
+                format!("\x1B[2J\x1B[1;1H{board}\n\n{name} ({player}):\n")
+            }
+        },
+    )
+}
@@ -0,0 +1,138 @@
+//! Hosting games over TCP. See [`protocol`](crate::protocol) for the
+//! messages.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use super::{
+    lobby::{Action, Event, Lobby, Notice},
+    protocol::{ClientMessage, ServerMessage},
+    Game,
+};
+
+pub struct Server {
+    listener: TcpListener,
+    lobby: Lobby,
+}
+
+impl Server {
+    /// Listens on `address`, closing games that have been idle for
+    /// `idle_timeout`.
+    pub fn bind(address: impl ToSocketAddrs, idle_timeout: Duration) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            lobby: Lobby::new(idle_timeout),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Hosts `game` for clients to join, returning its ID.
+    pub fn host(&mut self, game: Game) -> u32 {
+        self.lobby.create(game)
+    }
+
+    /// Accepts connections and hosts their games. Connections that fail
+    /// before they're set up are logged and skipped, so they don't take the
+    /// games down with them.
+    pub fn run(self) -> io::Result<()> {
+        let (sender, events) = mpsc::channel();
+        let lobby = self.lobby;
+        thread::spawn(move || lobby.run(events));
+
+        for (id, stream) in self.listener.incoming().enumerate() {
+            if let Err(e) = stream.and_then(|stream| connect(stream, id, sender.clone())) {
+                eprintln!("Couldn't accept a connection: {e}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Starts passing a client's messages on to the lobby, and sending it back
+/// whatever the lobby has for it.
+fn connect(stream: TcpStream, id: usize, events: Sender<Event>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let (sender, notices) = mpsc::channel();
+    if events.send(Event::Connected(id, sender)).is_err() {
+        return Ok(());
+    }
+
+    thread::spawn(move || {
+        for notice in notices {
+            if writeln!(writer, "{}", ServerMessage::from(notice)).is_err() {
+                break;
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while matches!(reader.read_line(&mut line), Ok(1..)) {
+            let event = match line.parse::<ClientMessage>() {
+                Ok(message) => Event::Action(id, message.into()),
+                // Errors get sent back through the lobby, so that they
+                // don't get mixed up with its messages.
+                Err(e) => Event::Invalid(id, e.to_string()),
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+            line.clear();
+        }
+        let _ = events.send(Event::Disconnected(id));
+    });
+
+    Ok(())
+}
+
+impl From<ClientMessage> for Action {
+    fn from(message: ClientMessage) -> Self {
+        match message {
+            ClientMessage::Create { dim, players } => Action::Create { dim, players },
+            ClientMessage::List => Action::List,
+            ClientMessage::Join(game) => Action::Join(game),
+            ClientMessage::Rejoin { game, token } => Action::Rejoin { game, token },
+            ClientMessage::Spectate(game) => Action::Spectate(game),
+            ClientMessage::Move(coords) => Action::Place(coords),
+        }
+    }
+}
+
+impl From<Notice> for ServerMessage {
+    fn from(notice: Notice) -> Self {
+        match notice {
+            Notice::Created(game) => ServerMessage::Created(game),
+            Notice::Games(games) => ServerMessage::Games(games),
+            Notice::Joined {
+                game,
+                player,
+                dim,
+                players,
+                token,
+            } => ServerMessage::Join {
+                player,
+                dim,
+                players,
+                game,
+                token,
+            },
+            Notice::Spectating { game, dim, players } => {
+                ServerMessage::Spectate { game, dim, players }
+            }
+            Notice::Update { moves, .. } => {
+                ServerMessage::State(moves.into_iter().map(|(coords, _)| coords).collect())
+            }
+            Notice::Outcome { outcome, .. } => ServerMessage::Result(outcome),
+            Notice::Error(message) => ServerMessage::Error(message),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Displaying part of a board, for when the whole thing is too big to look at.
+
+use std::{collections::HashMap, fmt::Display};
+
+use itertools::Itertools;
+
+use super::{
+    board::{axis_name, Direction},
+    game::display_board,
+    Board, DisplayOptions, Game, Highlight, Piece, SliceError,
+};
+
+impl Game {
+    /// Displays the part of the board where the coordinates in `fixed` (in the
+    /// order players enter them) that are `Some` have that value. For example,
+    /// `[None, None, Some(2), Some(0)]` shows a single 2D board.
+    pub fn display_slice(
+        &self,
+        fixed: &[Option<usize>],
+        options: &DisplayOptions,
+    ) -> Result<String, SliceError> {
+        if fixed.len() != self.dim() {
+            return Err(SliceError::WrongDimension);
+        }
+
+        let fixed = self.map_player_order(fixed.to_vec());
+        let board = self.board.slice(&fixed).ok_or(SliceError::OutOfBounds)?;
+        Ok(self.display_part(&board, &fixed, options))
+    }
+
+    /// Displays each of the innermost 2D boards that has a piece on it, headed
+    /// by the coordinates that pick it out.
+    pub fn display_occupied_slices(&self, options: &DisplayOptions) -> String {
+        let names: Vec<_> = (0..self.dim()).map(axis_name).collect();
+
+        let slices: Vec<_> = self
+            .sub_boards()
+            .into_iter()
+            .filter(|(_, board)| !is_empty(board))
+            .map(|(prefix, board)| {
+                let fixed: Vec<_> = prefix
+                    .iter()
+                    .map(|i| Some(*i))
+                    .pad_using(self.dim(), |_| None)
+                    .collect();
+                let heading = self
+                    .map_player_order(fixed.clone())
+                    .iter()
+                    .zip(&names)
+                    .filter_map(|(coord, name)| coord.map(|coord| format!("{name}={coord}")))
+                    .join(" ");
+
+                format!("{heading}\n{}", self.display_part(board, &fixed, options))
+            })
+            .collect();
+
+        if slices.is_empty() {
+            "The board is empty".to_string()
+        } else {
+            slices.join("\n\n")
+        }
+    }
+
+    /// Displays a map of the innermost 2D boards (one character each, showing
+    /// whether or not it has any pieces), followed by the ones with pieces
+    /// on them.
+    pub(crate) fn display_compact(&self, options: &DisplayOptions) -> String {
+        let outer_levels = self.dim() - 2;
+
+        let mut overview =
+            Board::<Overview>::new(vec![self.width(); outer_levels], Overview(false));
+        for (prefix, board) in self.sub_boards() {
+            overview[prefix.into()] = Overview(!is_empty(board)).into();
+        }
+
+        // Highlight the boards with highlighted pieces
+        let mut highlights = HashMap::new();
+        for (coords, highlight) in &options.highlights {
+            let coords = coords.iter().take(outer_levels).copied().collect();
+            // The winning line is more important than the last move
+            let entry = highlights.entry(coords).or_insert(*highlight);
+            if *highlight == Highlight::WinningLine {
+                *entry = *highlight;
+            }
+        }
+
+        let overview = display_board(
+            &overview,
+            Direction::Horizontal,
+            &self.axis_names()[..outer_levels],
+            &DisplayOptions {
+                highlights,
+                ..options.clone()
+            },
+        );
+        format!("{overview}\n\n{}", self.display_occupied_slices(options))
+    }
+
+    /// Each of the innermost 2D boards (or the whole board, if it has less
+    /// than 3 dimensions), along with the coordinates that pick it out.
+    fn sub_boards(&self) -> Vec<(Vec<usize>, &Board<Piece>)> {
+        let outer_levels = self.dim().saturating_sub(2);
+        let prefixes: Vec<Vec<usize>> = if outer_levels == 0 {
+            vec![vec![]]
+        } else {
+            (0..outer_levels)
+                .map(|_| 0..self.width())
+                .multi_cartesian_product()
+                .collect()
+        };
+
+        prefixes
+            .into_iter()
+            .map(|prefix| {
+                let board = &self.board[prefix.clone().into()];
+                (prefix, board)
+            })
+            .collect()
+    }
+
+    /// Displays `board`, which is the part of the whole board picked out by
+    /// `fixed` (in the order the board stores the axes in).
+    fn display_part(
+        &self,
+        board: &Board<Piece>,
+        fixed: &[Option<usize>],
+        options: &DisplayOptions,
+    ) -> String {
+        let names = self
+            .axis_names()
+            .into_iter()
+            .zip(fixed)
+            .filter(|(_, fixed)| fixed.is_none())
+            .map(|(name, _)| name)
+            .collect_vec();
+
+        // Only keep the highlights in the slice, without the fixed coordinates
+        let highlights = options
+            .highlights
+            .iter()
+            .filter(|(coords, _)| {
+                coords
+                    .iter()
+                    .zip(fixed)
+                    .all(|(coord, fixed)| fixed.is_none_or(|fixed| fixed == *coord))
+            })
+            .map(|(coords, highlight)| {
+                let coords = coords
+                    .iter()
+                    .zip(fixed)
+                    .filter(|(_, fixed)| fixed.is_none())
+                    .map(|(coord, _)| *coord)
+                    .collect();
+                (coords, *highlight)
+            })
+            .collect();
+
+        // Keep the outermost axis that's left going the same way it does on
+        // the whole board, so the slice looks like the part of the board it
+        // came from.
+        let direction = fixed
+            .iter()
+            .position(|fixed| fixed.is_none())
+            .map_or(Direction::Horizontal, Direction::of_level);
+
+        display_board(
+            board,
+            direction,
+            &names,
+            &DisplayOptions {
+                highlights,
+                ..options.clone()
+            },
+        )
+    }
+}
+
+fn is_empty(board: &Board<Piece>) -> bool {
+    board.flatten().iter().all(|piece| piece.player.is_none())
+}
+
+/// A whole 2D board, shown as a single character in compact mode. It's `true`
+/// if there are any pieces on the board.
+#[derive(Debug, Clone)]
+struct Overview(bool);
+
+/// The alternate form (`{:#}`) is plain ASCII, without any colors.
+impl Display for Overview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.0, f.alternate()) {
+            (true, false) => f.write_str("\x1b[1m■\x1b[0m"),
+            (false, false) => f.write_str("\x1b[2;90m·\x1b[0m"),
+            (true, true) => f.write_str("#"),
+            (false, true) => f.write_str("."),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Ways of choosing moves, so that each seat in a game can be played by a
+//! person, the built-in AIs, an external [`Engine`] (which might be on
+//! another machine), or a script.
+
+use std::{
+    collections::{hash_map::RandomState, VecDeque},
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, Read, Stdout, Write},
+    iter::repeat_n,
+    time::Duration,
+};
+
+use itertools::Itertools;
+
+use super::{board::Idx, engine::Engine, Game, Player};
+
+/// Something that makes moves for a player.
+pub trait Strategy {
+    /// Called before each game the strategy plays in.
+    fn new_game(&mut self, _game: &Game) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called before [`choose`](Strategy::choose) when the player is on a
+    /// clock, with the most time the move should take.
+    fn set_time_limit(&mut self, _limit: Duration) {}
+
+    /// Chooses a move for `player` in `game`, in the order the board stores
+    /// coordinates in (like [`Game::hint`]).
+    fn choose(&mut self, game: &Game, player: Player) -> io::Result<Idx>;
+}
+
+/// The built-in AI, which plays whatever [`Game::hint`] suggests.
+pub struct Hint;
+
+impl Strategy for Hint {
+    fn choose(&mut self, game: &Game, player: Player) -> io::Result<Idx> {
+        game.hint(player).ok_or_else(no_moves)
+    }
+}
+
+/// Plays any empty cell, each as likely as the others.
+pub struct Random(Rng);
+
+impl Random {
+    pub fn new() -> Self {
+        Self(Rng::new())
+    }
+
+    /// Makes the same moves every time it's given the same `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self(Rng::with_seed(seed))
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Random {
+    fn choose(&mut self, game: &Game, _player: Player) -> io::Result<Idx> {
+        self.0
+            .pick(game.legal_moves().collect())
+            .ok_or_else(no_moves)
+    }
+}
+
+/// Wins if it can, blocks an opponent's win if it has to, and otherwise plays
+/// a random empty cell.
+pub struct Greedy(Rng);
+
+impl Greedy {
+    pub fn new() -> Self {
+        Self(Rng::new())
+    }
+
+    /// Makes the same moves every time it's given the same `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self(Rng::with_seed(seed))
+    }
+}
+
+impl Default for Greedy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Greedy {
+    fn choose(&mut self, game: &Game, player: Player) -> io::Result<Idx> {
+        let empty: Vec<_> = game.legal_moves().collect();
+        game.urgent_move(player, &empty)
+            .or_else(|| self.0.pick(empty))
+            .ok_or_else(no_moves)
+    }
+}
+
+/// Wins or blocks like [`Greedy`], and otherwise plays the empty cell on the
+/// most lines, which are the ones in the center and along the diagonals.
+pub struct Center(Rng);
+
+impl Center {
+    pub fn new() -> Self {
+        Self(Rng::new())
+    }
+
+    /// Makes the same moves every time it's given the same `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self(Rng::with_seed(seed))
+    }
+}
+
+impl Default for Center {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Center {
+    fn choose(&mut self, game: &Game, player: Player) -> io::Result<Idx> {
+        let empty: Vec<_> = game.legal_moves().collect();
+        if let Some(coords) = game.urgent_move(player, &empty) {
+            return Ok(coords);
+        }
+        // Any of the best cells, so that games aren't all the same
+        let best = empty
+            .into_iter()
+            .max_set_by_key(|coords| lines_through(coords, game.width()));
+        self.0.pick(best).ok_or_else(no_moves)
+    }
+}
+
+/// Someone typing moves, one per line, with the coordinates in the order
+/// players enter them in separated by spaces (or `quit` to stop playing).
+/// Moves that can't be made are asked for again.
+pub struct Human<R, W> {
+    input: R,
+    output: W,
+    prompt: Box<Prompt>,
+}
+
+/// What to ask for each move with, like the board and whose turn it is
+type Prompt = dyn FnMut(&Game, Player) -> String;
+
+impl Human<Lines, Stdout> {
+    /// Someone at this terminal. Standard input is only locked while a line
+    /// is being read, so any number of people can take turns at it.
+    pub fn stdin() -> Self {
+        let read_line = || {
+            let mut line = String::new();
+            Ok((io::stdin().read_line(&mut line)? > 0).then_some(line))
+        };
+        Self::new(Lines::new(read_line), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Human<R, W> {
+    /// Someone typing into `input`, and being prompted on `output` with
+    /// their symbol.
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            prompt: Box::new(|_, player| format!("{player:#}: ")),
+        }
+    }
+
+    /// Asks for each move with `prompt` instead of just the player's symbol.
+    pub fn with_prompt(mut self, prompt: impl FnMut(&Game, Player) -> String + 'static) -> Self {
+        self.prompt = Box::new(prompt);
+        self
+    }
+}
+
+impl<R: BufRead, W: Write> Strategy for Human<R, W> {
+    fn choose(&mut self, game: &Game, player: Player) -> io::Result<Idx> {
+        write!(self.output, "{}", (self.prompt)(game, player))?;
+        loop {
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "No more input",
+                ));
+            }
+            if line.trim() == "quit" {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Quit"));
+            }
+
+            let Ok(coords) = line.split_whitespace().map(|x| x.parse()).collect() else {
+                write!(self.output, "Invalid coordinates, try again: ")?;
+                continue;
+            };
+            let coords = game.map_player_coords(coords);
+            if game.is_legal(&coords) {
+                return Ok(coords);
+            }
+            write!(self.output, "That cell isn't free, try again: ")?;
+        }
+    }
+}
+
+/// Input for a [`Human`] that comes a line at a time from somewhere other
+/// than a reader, like a line editor.
+pub struct Lines {
+    read_line: Box<dyn FnMut() -> io::Result<Option<String>>>,
+    /// The rest of the line being read
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl Lines {
+    /// Reads each line with `read_line`, which returns `None` once there are
+    /// no more.
+    pub fn new(read_line: impl FnMut() -> io::Result<Option<String>> + 'static) -> Self {
+        Self {
+            read_line: Box::new(read_line),
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for Lines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for Lines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position == self.buffer.len() {
+            self.buffer = match (self.read_line)()? {
+                Some(mut line) => {
+                    if !line.ends_with('\n') {
+                        line.push('\n');
+                    }
+                    line.into_bytes()
+                }
+                None => Vec::new(),
+            };
+            self.position = 0;
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.buffer.len());
+    }
+}
+
+/// Plays a list of moves, like a game being replayed.
+pub struct Scripted(VecDeque<Idx>);
+
+impl Scripted {
+    /// Plays `moves` in order, with the coordinates of each in the order
+    /// players enter them in.
+    pub fn new(moves: impl IntoIterator<Item = Idx>) -> Self {
+        Self(moves.into_iter().collect())
+    }
+}
+
+impl Strategy for Scripted {
+    fn choose(&mut self, game: &Game, _player: Player) -> io::Result<Idx> {
+        let coords = self
+            .0
+            .pop_front()
+            .ok_or_else(|| io::Error::other("The script is out of moves"))?;
+        Ok(game.map_player_coords(coords))
+    }
+}
+
+/// The number of lines (rows, columns and diagonals) through the cell at
+/// `coords`.
+fn lines_through(coords: &Idx, width: usize) -> usize {
+    // Each line goes up or down along each axis it isn't flat on. Only the
+    // directions that go up along the first axis that isn't flat are counted,
+    // since the others are the same lines the other way around.
+    repeat_n([-1, 0, 1], coords.len())
+        .multi_cartesian_product()
+        .filter(|direction| direction.iter().find(|d| **d != 0) == Some(&1))
+        .filter(|direction| {
+            // How far along the line the cell is on each axis that isn't flat
+            direction
+                .iter()
+                .zip(coords)
+                .filter(|(d, _)| **d != 0)
+                .map(|(d, x)| if *d == 1 { *x } else { width - 1 - x })
+                .all_equal()
+        })
+        .count()
+}
+
+fn no_moves() -> io::Error {
+    io::Error::other("No moves left")
+}
+
+/// A small (xorshift) random number generator, so that strategies can be
+/// seeded to make the same moves every time.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    fn with_seed(seed: u64) -> Self {
+        // Xorshift gets stuck on zero
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn pick<T>(&mut self, mut items: Vec<T>) -> Option<T> {
+        if items.is_empty() {
+            return None;
+        }
+        let i = (self.next() % items.len() as u64) as usize;
+        Some(items.swap_remove(i))
+    }
+}
+
+impl Strategy for Engine {
+    fn new_game(&mut self, game: &Game) -> io::Result<()> {
+        Engine::new_game(self, game)
+    }
+
+    fn set_time_limit(&mut self, limit: Duration) {
+        self.time_limit = Some(limit);
+    }
+
+    fn choose(&mut self, game: &Game, _player: Player) -> io::Result<Idx> {
+        let movetime = match self.time_limit.take() {
+            Some(limit) => self.movetime().min(limit),
+            None => self.movetime(),
+        };
+        self.best_move(game, movetime)
+    }
+}
@@ -0,0 +1,230 @@
+//! Rendering boards as SVG images.
+//!
+//! The image is laid out on the same grid of characters as
+//! [`Game::display_with`], so the sub-boards nest the same way they do in the
+//! terminal.
+
+use std::fmt::Write;
+
+use super::{DisplayOptions, Game, Highlight, Piece, Player};
+
+/// The size of one character of the text display, in pixels. Pieces are
+/// separated by a space horizontally but not vertically, so this makes the
+/// innermost boards square.
+const CHAR_WIDTH: usize = 16;
+const CHAR_HEIGHT: usize = 32;
+
+const BACKGROUND: &str = "#ffffff";
+const BOARD: &str = "#f3f4f6";
+const EMPTY: &str = "#d1d5db";
+const LABEL: &str = "#6b7280";
+const HIGHLIGHT: &str = "#ef4444";
+
+impl Game {
+    /// Renders the board as an SVG image. `options.plain` is ignored.
+    pub fn to_svg(&self, options: &DisplayOptions) -> String {
+        let (width, height, body) = self.svg_body(options);
+        format!("{}{body}</svg>\n", svg_header(width, height))
+    }
+
+    /// Renders the game so far as a sequence of SVG images, one after each
+    /// move, with the move (and the winning line, if there is one)
+    /// highlighted. `options.highlights` is ignored.
+    pub fn replay_svg_frames(&self, options: &DisplayOptions) -> Vec<String> {
+        self.replay()
+            .map(|game| {
+                game.to_svg(&DisplayOptions {
+                    highlights: game.highlights(),
+                    ..options.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Like [`Game::replay_svg_frames`], but as a single animated SVG image
+    /// that shows each move for `seconds_per_move` and then loops.
+    pub fn replay_svg(&self, options: &DisplayOptions, seconds_per_move: f64) -> String {
+        let frames: Vec<_> = self
+            .replay()
+            .map(|game| {
+                game.svg_body(&DisplayOptions {
+                    highlights: game.highlights(),
+                    ..options.clone()
+                })
+            })
+            .collect();
+        let (width, height) = frames
+            .first()
+            .map_or((0, 0), |(width, height, _)| (*width, *height));
+
+        let n = frames.len();
+        let mut svg = svg_header(width, height);
+        for (i, (_, _, body)) in frames.into_iter().enumerate() {
+            // Each frame is only visible for its slice of the animation.
+            let _ = writeln!(
+                svg,
+                r#"<g visibility="hidden"><animate attributeName="visibility" values="hidden;visible;hidden" keyTimes="0;{};{}" calcMode="discrete" dur="{}s" repeatCount="indefinite"/>"#,
+                i as f64 / n as f64,
+                (i + 1) as f64 / n as f64,
+                seconds_per_move * n as f64,
+            );
+            svg.push_str(&body);
+            svg.push_str("</g>\n");
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The game after each of the moves so far.
+    fn replay(&self) -> impl Iterator<Item = Game> + '_ {
+        let mut game = Game::new(self.dim(), self.players().len() as u32);
+        self.moves().map(move |(coords, player)| {
+            game.place_piece(Piece::new(player), coords)
+                .expect("Replaying a valid move");
+            game.clone()
+        })
+    }
+
+    /// The contents of the SVG image, along with its width and height.
+    fn svg_body(&self, options: &DisplayOptions) -> (usize, usize, String) {
+        let layout = self.layout(options);
+        let labels = options.labels.then(|| self.labels(options.hide_padding));
+
+        let label_chars: Vec<_> = labels.iter().flat_map(|labels| labels.chars()).collect();
+        let columns = label_chars
+            .iter()
+            .map(|((_, column), _)| column + 1)
+            .fold(layout.width, usize::max);
+        let (width, height) = (columns * CHAR_WIDTH, layout.height * CHAR_HEIGHT);
+        let center = |(line, column): (usize, usize)| {
+            (
+                column * CHAR_WIDTH + CHAR_WIDTH / 2,
+                line * CHAR_HEIGHT + CHAR_HEIGHT / 2,
+            )
+        };
+
+        let mut svg = String::new();
+        // Writing to a `String` can't fail
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{BACKGROUND}"/>"#
+        );
+
+        // Shade each of the innermost 2D (or 1D) boards
+        for (first, last) in self.innermost_boards(options) {
+            let (x1, y1) = center(first);
+            let (x2, y2) = center(last);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="6" fill="{BOARD}"/>"#,
+                x1 - CHAR_WIDTH,
+                y1 - CHAR_HEIGHT / 2,
+                x2 - x1 + 2 * CHAR_WIDTH,
+                y2 - y1 + CHAR_HEIGHT,
+            );
+        }
+
+        for (coords, position) in &layout.cells {
+            let (x, y) = center(*position);
+            let radius = CHAR_HEIGHT / 2 - 2;
+
+            match options.highlights.get(coords) {
+                Some(Highlight::WinningLine) => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<circle cx="{x}" cy="{y}" r="{radius}" fill="{HIGHLIGHT}" fill-opacity="0.25" stroke="{HIGHLIGHT}" stroke-width="2"/>"#
+                    );
+                }
+                Some(Highlight::LastMove) => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<circle cx="{x}" cy="{y}" r="{radius}" fill="none" stroke="{HIGHLIGHT}" stroke-width="2" stroke-dasharray="4 3"/>"#
+                    );
+                }
+                Some(Highlight::Target) => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<circle cx="{x}" cy="{y}" r="{radius}" fill="{HIGHLIGHT}" fill-opacity="0.1"/>"#
+                    );
+                }
+                None => (),
+            }
+
+            match self
+                .board
+                .get(coords.clone())
+                .and_then(|piece| piece.player)
+            {
+                Some(player) => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{x}" y="{y}" font-size="{}" fill="{}">{}</text>"#,
+                        CHAR_HEIGHT * 3 / 4,
+                        color(player),
+                        escape(player.symbol()),
+                    );
+                }
+                None => {
+                    let _ = writeln!(svg, r#"<circle cx="{x}" cy="{y}" r="3" fill="{EMPTY}"/>"#);
+                }
+            }
+        }
+
+        for (position, c) in label_chars {
+            let (x, y) = center(position);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x}" y="{y}" font-size="{}" font-weight="normal" fill="{LABEL}">{}</text>"#,
+                CHAR_HEIGHT / 2,
+                escape(c),
+            );
+        }
+
+        (width, height, svg)
+    }
+
+    /// The first and last positions of the pieces in each of the innermost
+    /// 2D boards (or the whole board, if it's 1D).
+    fn innermost_boards(&self, options: &DisplayOptions) -> Vec<((usize, usize), (usize, usize))> {
+        let outer_levels = self.dim().saturating_sub(2);
+
+        let mut boards: Vec<(Vec<usize>, _)> = Vec::new();
+        for (coords, position) in self.layout(options).cells {
+            let key: Vec<_> = coords.iter().take(outer_levels).copied().collect();
+            match boards.last_mut() {
+                // The cells of each board are next to each other in the layout
+                Some((last_key, (_, last))) if *last_key == key => *last = position,
+                _ => boards.push((key, (position, position))),
+            }
+        }
+
+        boards.into_iter().map(|(_, span)| span).collect()
+    }
+}
+
+fn svg_header(width: usize, height: usize) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace" font-weight="bold" text-anchor="middle" dominant-baseline="central">
+"#
+    )
+}
+
+/// The same colors as [`Player::with_color`].
+fn color(player: Player) -> &'static str {
+    match player.symbol() {
+        'X' => "#3b82f6",
+        'O' => "#eab308",
+        'F' => "#d946ef",
+        _ => "#111827",
+    }
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '&' => "&amp;".to_string(),
+        c => c.to_string(),
+    }
+}
@@ -0,0 +1,278 @@
+//! Scheduling tournaments between participants (people or strategies), and
+//! keeping track of how they're doing.
+//!
+//! Every pairing in a round plays one two-player game for each of the
+//! tournament's board sizes, taking turns going first.
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Everyone plays everyone else once.
+    RoundRobin,
+    /// The given number of rounds, each pairing participants with similar
+    /// scores who haven't played each other yet.
+    Swiss(u32),
+}
+
+/// Like a [`GameOutcome`](crate::GameOutcome), but with the index of the
+/// winning participant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win(usize),
+    Draw,
+}
+
+/// A game between two participants (given by index).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pairing {
+    /// The round the game is in, from 1
+    pub round: u32,
+    /// Who moves first
+    pub first: usize,
+    pub second: usize,
+    pub dim: usize,
+    pub outcome: Option<Outcome>,
+}
+
+/// How a participant is doing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub participant: usize,
+    /// One for each win and bye, and half for each draw
+    pub points: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The number of rounds the participant sat out of (in Swiss tournaments
+    /// with an odd number of participants)
+    pub byes: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    participants: usize,
+    dims: Vec<usize>,
+    format: Format,
+    pairings: Vec<Pairing>,
+    /// The rounds each participant had a bye in
+    byes: Vec<Vec<u32>>,
+    round: u32,
+}
+
+impl Tournament {
+    /// Schedules a tournament between `participants` participants, playing
+    /// games with each number of dimensions in `dims`. Round robins are
+    /// scheduled all at once, while each round of a Swiss tournament is
+    /// scheduled when the one before it is over.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 2 participants or no board sizes.
+    pub fn new(participants: usize, dims: Vec<usize>, format: Format) -> Self {
+        assert!(
+            participants >= 2,
+            "Tournaments need at least 2 participants"
+        );
+        assert!(!dims.is_empty(), "Tournaments need at least one board size");
+
+        let mut tournament = Self {
+            participants,
+            dims,
+            format,
+            pairings: Vec::new(),
+            byes: vec![Vec::new(); participants],
+            round: 0,
+        };
+        match format {
+            Format::RoundRobin => tournament.schedule_round_robin(),
+            Format::Swiss(_) => tournament.schedule_swiss_round(),
+        }
+        tournament
+    }
+
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Every game scheduled so far, in the order they're meant to be played.
+    pub fn pairings(&self) -> &[Pairing] {
+        &self.pairings
+    }
+
+    /// The index of the next game to play, or `None` if the tournament is
+    /// over.
+    pub fn next_game(&self) -> Option<usize> {
+        self.pairings.iter().position(|p| p.outcome.is_none())
+    }
+
+    /// Records the outcome of a game, scheduling the next round if it was the
+    /// last game in its round.
+    pub fn record(&mut self, game: usize, outcome: Outcome) {
+        self.pairings[game].outcome = Some(outcome);
+        if let Format::Swiss(rounds) = self.format {
+            if self.next_game().is_none() && self.round < rounds {
+                self.schedule_swiss_round();
+            }
+        }
+    }
+
+    /// Everyone's results, best first. Ties are broken by the number of wins.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<_> = (0..self.participants)
+            .map(|participant| Standing {
+                participant,
+                points: 0.0,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                byes: self.byes[participant].len() as u32,
+            })
+            .collect();
+
+        for pairing in &self.pairings {
+            match pairing.outcome {
+                Some(Outcome::Win(winner)) => {
+                    let loser = pairing.first + pairing.second - winner;
+                    standings[winner].wins += 1;
+                    standings[loser].losses += 1;
+                }
+                Some(Outcome::Draw) => {
+                    standings[pairing.first].draws += 1;
+                    standings[pairing.second].draws += 1;
+                }
+                None => (),
+            }
+        }
+        for standing in &mut standings {
+            // A bye is worth winning every game in the round
+            let bye_points = standing.byes as usize * self.dims.len();
+            standing.points =
+                standing.wins as f64 + standing.draws as f64 / 2.0 + bye_points as f64;
+        }
+
+        standings.sort_by(|a, b| {
+            b.points
+                .partial_cmp(&a.points)
+                .unwrap_or(Ordering::Equal)
+                .then(b.wins.cmp(&a.wins))
+                .then(a.participant.cmp(&b.participant))
+        });
+        standings
+    }
+
+    /// Adds a game for each board size between `a` and `b`, with `a` going
+    /// first in the first one.
+    fn pair(&mut self, a: usize, b: usize) {
+        for (i, &dim) in self.dims.iter().enumerate() {
+            let (first, second) = if i % 2 == 0 { (a, b) } else { (b, a) };
+            self.pairings.push(Pairing {
+                round: self.round,
+                first,
+                second,
+                dim,
+                outcome: None,
+            });
+        }
+    }
+
+    /// Schedules every round with the circle method: one participant stays
+    /// put while the rest rotate around them.
+    fn schedule_round_robin(&mut self) {
+        // With an odd number of participants, whoever is paired with the
+        // extra one sits the round out
+        let seats = self.participants + self.participants % 2;
+        let mut order: Vec<_> = (0..seats).collect();
+        for round in 0..seats - 1 {
+            self.round += 1;
+            for i in 0..seats / 2 {
+                let (a, b) = (order[i], order[seats - 1 - i]);
+                if a >= self.participants || b >= self.participants {
+                    continue;
+                }
+                // Take turns going first against the participant staying put
+                if i == 0 && round % 2 == 1 {
+                    self.pair(b, a);
+                } else {
+                    self.pair(a, b);
+                }
+            }
+            order[1..].rotate_right(1);
+        }
+    }
+
+    /// Pairs participants with the most points first, avoiding rematches
+    /// where possible.
+    fn schedule_swiss_round(&mut self) {
+        self.round += 1;
+        let mut ranked: Vec<_> = self
+            .standings()
+            .into_iter()
+            .map(|standing| standing.participant)
+            .collect();
+
+        if ranked.len() % 2 == 1 {
+            // The lowest-ranked participant who hasn't had a bye yet
+            let fewest = self.byes.iter().map(Vec::len).min().unwrap();
+            let bye = ranked
+                .iter()
+                .rposition(|p| self.byes[*p].len() == fewest)
+                .unwrap();
+            let participant = ranked.remove(bye);
+            self.byes[participant].push(self.round);
+        }
+
+        // Everyone plays the next person down if there's no way around
+        // rematches
+        let pairs = self
+            .pair_up(&ranked)
+            .unwrap_or_else(|| ranked.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+        for (a, b) in pairs {
+            // Whoever has gone first less often goes first
+            if self.times_first(b) < self.times_first(a) {
+                self.pair(b, a);
+            } else {
+                self.pair(a, b);
+            }
+        }
+    }
+
+    /// Pairs up `participants` (in order of rank) so that nobody plays anyone
+    /// twice, pairing each with the highest-ranked opponent possible.
+    fn pair_up(&self, participants: &[usize]) -> Option<Vec<(usize, usize)>> {
+        let Some((&a, rest)) = participants.split_first() else {
+            return Some(Vec::new());
+        };
+        rest.iter().enumerate().find_map(|(i, &b)| {
+            if self.have_played(a, b) {
+                return None;
+            }
+            let mut others = rest.to_vec();
+            others.remove(i);
+            let mut pairs = self.pair_up(&others)?;
+            pairs.insert(0, (a, b));
+            Some(pairs)
+        })
+    }
+
+    fn have_played(&self, a: usize, b: usize) -> bool {
+        self.pairings
+            .iter()
+            .any(|p| (p.first, p.second) == (a, b) || (p.first, p.second) == (b, a))
+    }
+
+    fn times_first(&self, participant: usize) -> usize {
+        self.pairings
+            .iter()
+            .filter(|p| p.first == participant)
+            .count()
+    }
+}
@@ -0,0 +1,208 @@
+//! A full-screen terminal interface, where moves are chosen by moving a cursor
+//! around the board (or clicking on a cell) instead of typing coordinates.
+//!
+//! The arrow keys move the cursor within the innermost 2D board, and WASD
+//! moves it across the next pair of axes out. For boards with more than four
+//! dimensions, tab cycles through which pair of outer axes WASD controls.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEventKind,
+    },
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use nd_tic_tac_toe::{DisplayOptions, Game, GameOutcome, Piece};
+
+/// The line the board starts on, leaving space for the status messages.
+const BOARD_TOP: u16 = 2;
+
+const HELP: &str = "Arrows/WASD: move, Tab: switch axes, Enter/click: place, u: undo, q: quit";
+
+/// Plays `game` until it's over or the players quit, then restores the
+/// terminal and prints the final board.
+pub fn run(mut game: Game, mut options: DisplayOptions) -> io::Result<()> {
+    // The cursor moves around the whole board, so it all needs to be shown
+    options.compact = false;
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
+
+    let result = Tui::new(&mut game, options.clone()).run(&mut stdout);
+
+    execute!(stdout, Show, DisableMouseCapture, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    println!(
+        "{}",
+        game.display_with(&DisplayOptions {
+            highlights: game.highlights(),
+            ..options
+        })
+    );
+    match result? {
+        Some(GameOutcome::Win(winner)) => println!("\x1b[1m{}\x1b[1m wins!\x1b[0m", winner),
+        Some(GameOutcome::Draw) => println!("It's a draw!"),
+        None => println!("Game over. Goodbye!"),
+    }
+    Ok(())
+}
+
+struct Tui<'a> {
+    game: &'a mut Game,
+    options: DisplayOptions,
+    cursor: VecDeque<usize>,
+    /// Pairs of `(horizontal, vertical)` axes, from the innermost outwards.
+    /// The outermost pair may be missing its vertical axis.
+    axes: Vec<(usize, Option<usize>)>,
+    /// Which pair of axes WASD moves across.
+    outer_axes: usize,
+    message: String,
+}
+
+impl<'a> Tui<'a> {
+    fn new(game: &'a mut Game, options: DisplayOptions) -> Self {
+        let dim = game.dim();
+
+        // The board alternates between horizontal and vertical axes, starting
+        // with a horizontal one on the outside.
+        let axes: Vec<_> = (0..dim)
+            .rev()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|chunk| match *chunk {
+                [a, b] if a % 2 == 0 => (a, Some(b)),
+                [a, b] => (b, Some(a)),
+                [a] => (a, None),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        Self {
+            cursor: vec![0; dim].into(),
+            outer_axes: 1.min(axes.len() - 1),
+            axes,
+            game,
+            options,
+            message: HELP.to_string(),
+        }
+    }
+
+    /// Returns how the game ended, unless the players quit first.
+    fn run(&mut self, stdout: &mut impl Write) -> io::Result<Option<GameOutcome>> {
+        loop {
+            self.draw(stdout)?;
+
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => match key.code {
+                    KeyCode::Left => self.move_cursor(0, -1, 0),
+                    KeyCode::Right => self.move_cursor(0, 1, 0),
+                    KeyCode::Up => self.move_cursor(0, 0, -1),
+                    KeyCode::Down => self.move_cursor(0, 0, 1),
+                    KeyCode::Char('a') => self.move_cursor(self.outer_axes, -1, 0),
+                    KeyCode::Char('d') => self.move_cursor(self.outer_axes, 1, 0),
+                    KeyCode::Char('w') => self.move_cursor(self.outer_axes, 0, -1),
+                    KeyCode::Char('s') => self.move_cursor(self.outer_axes, 0, 1),
+                    KeyCode::Tab if self.axes.len() > 2 => {
+                        self.outer_axes = self.outer_axes % (self.axes.len() - 1) + 1;
+                    }
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        if let Some(outcome) = self.place() {
+                            return Ok(Some(outcome));
+                        }
+                    }
+                    KeyCode::Char('u') => match self.game.undo() {
+                        Some((coords, _)) => {
+                            self.message = "Undid last move".to_string();
+                            self.cursor = coords;
+                        }
+                        None => self.message = "No moves to undo".to_string(),
+                    },
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                    _ => (),
+                },
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    let Some(line) = mouse.row.checked_sub(BOARD_TOP) else {
+                        continue;
+                    };
+                    let layout = self.game.layout(&self.options);
+                    if let Some(coords) = layout.cell_at(line as usize, mouse.column as usize) {
+                        self.cursor = coords.clone();
+                        if let Some(outcome) = self.place() {
+                            return Ok(Some(outcome));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, axes: usize, dx: isize, dy: isize) {
+        let (horizontal, vertical) = self.axes[axes];
+        let max = self.game.width() - 1;
+        let mut step = |axis: usize, delta: isize| {
+            self.cursor[axis] = self.cursor[axis].saturating_add_signed(delta).min(max);
+        };
+
+        step(horizontal, dx);
+        if let Some(vertical) = vertical {
+            step(vertical, dy);
+        }
+    }
+
+    /// Places a piece at the cursor, returning the outcome if that ended the
+    /// game.
+    fn place(&mut self) -> Option<GameOutcome> {
+        let player = self.game.current_player();
+        match self
+            .game
+            .place_piece(Piece::new(player), self.cursor.clone())
+        {
+            Ok(_) => self.message = format!("Last move: {} at {:?}", player, self.cursor),
+            Err(e) => self.message = e.to_string(),
+        }
+        self.game.outcome()
+    }
+
+    fn draw(&self, stdout: &mut impl Write) -> io::Result<()> {
+        queue!(
+            stdout,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print(format!("{}: {}", self.game.current_player(), self.message))
+        )?;
+
+        let display = self.game.display_with(&DisplayOptions {
+            highlights: self.game.highlights(),
+            ..self.options.clone()
+        });
+        for (i, line) in display.lines().enumerate() {
+            queue!(stdout, MoveTo(0, BOARD_TOP + i as u16), Print(line))?;
+        }
+
+        // Draw the cursor on top of the piece it's over
+        let layout = self.game.layout(&self.options);
+        if let Some((line, column)) = layout.position(&self.cursor) {
+            let piece = self.game.piece(&self.cursor).unwrap();
+            queue!(
+                stdout,
+                MoveTo(column as u16, BOARD_TOP + line as u16),
+                SetAttribute(Attribute::Reverse),
+                Print(piece),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        stdout.flush()
+    }
+}
@@ -0,0 +1,268 @@
+//! Ultimate tic-tac-toe, where each cell of the board is a board of its own.
+//!
+//! Moves are made on the small boards, and winning one claims its cell on the
+//! big board, which is where the game is won. Where a move is made on its
+//! small board sends the next player to the small board in the same place on
+//! the big one, unless that board has already been won or is full, in which
+//! case they can play on any board.
+//!
+//! Like in a [`Game`], the big board and the small ones have the same number
+//! of dimensions, and are one wider than that. Moves are given by the
+//! coordinates of the small board on the big one, and of the cell on the
+//! small board, both in the order the boards store them in. This is also the
+//! order the axes are shown in, as if the whole thing were one board with
+//! twice the dimensions.
+
+use std::{collections::HashMap, fmt::Display};
+
+use super::{
+    board::{axis_name, Board, Direction, Idx},
+    game::display_board,
+    DisplayOptions, Game, GameOutcome, Highlight, Piece, PlacePieceError, Player,
+};
+
+#[derive(Debug, Clone)]
+pub struct Ultimate {
+    /// The small boards, one in each cell of the big one
+    boards: Board<Game>,
+    /// The big board, where the small boards that have been won are claimed
+    meta: Game,
+    history: Vec<Move>,
+}
+
+#[derive(Debug, Clone)]
+struct Move {
+    board: Idx,
+    cell: Idx,
+    /// Whether the move won its small board
+    claimed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    OutOfBounds,
+    Occupied,
+    /// The player was sent to a different small board.
+    WrongBoard,
+    /// The small board has already been won, or is full.
+    Closed,
+    /// The game is over.
+    Over,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::OutOfBounds => f.write_str("Out of bounds"),
+            MoveError::Occupied => f.write_str("Occupied"),
+            MoveError::WrongBoard => f.write_str("You have to play on the board you were sent to"),
+            MoveError::Closed => f.write_str("That board is already decided"),
+            MoveError::Over => f.write_str("The game is over"),
+        }
+    }
+}
+
+impl From<PlacePieceError> for MoveError {
+    fn from(e: PlacePieceError) -> Self {
+        match e {
+            PlacePieceError::OutOfBounds => MoveError::OutOfBounds,
+            PlacePieceError::Occupied => MoveError::Occupied,
+        }
+    }
+}
+
+impl Ultimate {
+    pub fn new(dim: usize, players: u32) -> Self {
+        let meta = Game::new(dim, players);
+        Self {
+            boards: Board::<Game>::new(vec![meta.width(); dim], Game::new(dim, players)),
+            meta,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.meta.dim()
+    }
+
+    pub fn width(&self) -> usize {
+        self.meta.width()
+    }
+
+    pub fn players(&self) -> &[Player] {
+        self.meta.players()
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.players()[self.history.len() % self.players().len()]
+    }
+
+    /// The big board, with a piece on each small board that's been won.
+    pub fn meta(&self) -> &Game {
+        &self.meta
+    }
+
+    /// The small board at `board` on the big one.
+    pub fn board(&self, board: Idx) -> Option<&Game> {
+        self.boards.get(board)
+    }
+
+    /// The small board the current player has to play on, or `None` if they
+    /// can play on any board that isn't decided yet.
+    pub fn sent_to(&self) -> Option<Idx> {
+        let cell = &self.history.last()?.cell;
+        (!self.is_closed(cell)).then(|| cell.clone())
+    }
+
+    /// All the moves played so far as `(board, cell, player)`, oldest first.
+    pub fn moves(&self) -> impl Iterator<Item = (Idx, Idx, Player)> + '_ {
+        self.history.iter().enumerate().map(|(i, m)| {
+            let player = self.players()[i % self.players().len()];
+            (m.board.clone(), m.cell.clone(), player)
+        })
+    }
+
+    /// Places the current player's piece at `cell` on the small board at
+    /// `board`, claiming the small board if that wins it.
+    pub fn make_move(&mut self, board: Idx, cell: Idx) -> Result<(), MoveError> {
+        if self.outcome().is_some() {
+            return Err(MoveError::Over);
+        }
+        if self.boards.get(board.clone()).is_none() {
+            return Err(MoveError::OutOfBounds);
+        }
+        if self.sent_to().is_some_and(|sent_to| sent_to != board) {
+            return Err(MoveError::WrongBoard);
+        }
+        if self.is_closed(&board) {
+            return Err(MoveError::Closed);
+        }
+
+        let player = self.current_player();
+        let small = self.boards.get_mut(board.clone()).unwrap();
+        small.place_piece(Piece::new(player), cell.clone())?;
+        let claimed = small.check_win(player);
+        if claimed {
+            self.meta
+                .place_piece(Piece::new(player), board.clone())
+                .expect("Small boards are only claimed once");
+        }
+        self.history.push(Move {
+            board,
+            cell,
+            claimed,
+        });
+        Ok(())
+    }
+
+    /// Takes back the most recent move (and the claim it made, if any),
+    /// returning where it was played and by whom. Returns `None` if no moves
+    /// have been played.
+    pub fn unmake_move(&mut self) -> Option<(Idx, Idx, Player)> {
+        let Move {
+            board,
+            cell,
+            claimed,
+        } = self.history.pop()?;
+        let (_, player) = self.boards.get_mut(board.clone()).unwrap().undo().unwrap();
+        if claimed {
+            self.meta.undo();
+        }
+        Some((board, cell, player))
+    }
+
+    /// Every move the current player can make, as `(board, cell)`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (Idx, Idx)> + '_ {
+        let boards: Vec<Idx> = match (self.outcome(), self.sent_to()) {
+            (Some(_), _) => Vec::new(),
+            (None, Some(board)) => vec![board],
+            (None, None) => self
+                .meta
+                .legal_moves()
+                .filter(|board| !self.is_closed(board))
+                .collect(),
+        };
+        boards.into_iter().flat_map(|board| {
+            let small = self.boards.get(board.clone()).unwrap();
+            small
+                .legal_moves()
+                .map(move |cell| (board.clone(), cell))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// How the game ended: someone won on the big board, or every small board
+    /// is decided. Returns `None` if the game isn't over yet.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if let Some((_, player)) = self.meta.last_move() {
+            if self.meta.check_win(player) {
+                return Some(GameOutcome::Win(player));
+            }
+        }
+        let all_closed = (0..self.width().pow(self.dim() as u32))
+            .map(|i| self.meta.get_coords(i))
+            .all(|board| self.is_closed(&board));
+        all_closed.then_some(GameOutcome::Draw)
+    }
+
+    /// Whether no more moves can be made on the small board at `board`,
+    /// because it's been won or is full.
+    fn is_closed(&self, board: &Idx) -> bool {
+        !self.meta.is_legal(board) || self.boards.get(board.clone()).is_none_or(Game::is_full)
+    }
+
+    /// Highlights the most recent move, the lines that won small boards, and
+    /// the cells of the small board the current player was sent to. The
+    /// coordinates are those of the small board followed by those of the
+    /// cell.
+    pub fn highlights(&self) -> HashMap<Idx, Highlight> {
+        let join = |board: &Idx, cell: &Idx| board.iter().chain(cell).copied().collect();
+        let mut highlights = HashMap::new();
+
+        if let Some(board) = self.sent_to() {
+            let small = self.boards.get(board.clone()).unwrap();
+            for cell in small.legal_moves() {
+                highlights.insert(join(&board, &cell), Highlight::Target);
+            }
+        }
+        for (board, player) in self.meta.moves() {
+            let small = self.boards.get(board.clone()).unwrap();
+            for cell in small.winning_line(player).unwrap_or_default() {
+                highlights.insert(join(&board, &cell), Highlight::WinningLine);
+            }
+        }
+        if let Some(m) = self.history.last() {
+            highlights.insert(join(&m.board, &m.cell), Highlight::LastMove);
+        }
+        highlights
+    }
+
+    /// Shows the big board with the small boards inside it, the same way as a
+    /// [`Game`] with twice the dimensions.
+    pub fn display_with(&self, options: &DisplayOptions) -> String {
+        let names: Vec<_> = (0..self.dim() * 2).map(axis_name).collect();
+        display_board(&self.cells(), Direction::Horizontal, &names, options)
+    }
+
+    /// The small boards, put together into one board.
+    fn cells(&self) -> Board<Piece> {
+        fn expand(boards: &Board<Game>) -> Board<Piece> {
+            match boards {
+                Board::Nd(boards) => Board::Nd(boards.iter().map(expand).collect()),
+                Board::Piece(game) => game.board.clone(),
+            }
+        }
+        expand(&self.boards)
+    }
+}
+
+/// The alternate form (`{:#}`) is plain ASCII, without any colors.
+impl Display for Ultimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let options = DisplayOptions {
+            plain: f.alternate(),
+            ..Default::default()
+        };
+        f.write_str(&self.display_with(&options))
+    }
+}
@@ -0,0 +1,294 @@
+//! Hosting games over WebSocket, with JSON messages, for browser front-ends.
+//!
+//! Each message is a JSON object with a `type`. Coordinates are in the order
+//! players enter them in (see [`Game::map_player_coords`]), and players are
+//! their symbols.
+//!
+//! From a client to the server:
+//!
+//! - `{"type": "create", "dim": 3, "players": 2}`: creates a new game.
+//! - `{"type": "list"}`: asks for the games being hosted.
+//! - `{"type": "join", "game": 1}`: takes the next free seat in a game. Without
+//!   a `game`, takes the first free seat in any game.
+//! - `{"type": "rejoin", "game": 1, "token": "3fa2..."}`: takes back a seat
+//!   after being disconnected.
+//! - `{"type": "spectate", "game": 1}`: watches a game without playing in it.
+//!   Spectators get every update and outcome the players do, but can't place
+//!   pieces.
+//! - `{"type": "place", "coords": [0, 1, 2]}`: places a piece for the
+//!   client's player in the game it joined.
+//!
+//! From the server to a client:
+//!
+//! - `{"type": "created", "game": 1}`: the game was created. Creating a game
+//!   doesn't join it.
+//! - `{"type": "games", "games": [{"id": 1, "dim": 3, "players": 2,
+//!   "open_seats": 1, "spectators": 0}]}`
+//! - `{"type": "joined", "game": 1, "player": "X", "dim": 3, "players": 2,
+//!   "token": "3fa2..."}`: the token is for rejoining.
+//! - `{"type": "spectating", "game": 1, "dim": 3, "players": 2}`
+//! - `{"type": "update", "game": 1, "moves": [{"player": "X", "coords": [0,
+//!   1, 2]}], "current_player": "O"}`: every move played so far, oldest
+//!   first. It's sent to everyone in the game (including spectators) when
+//!   someone joins, and after every move.
+//! - `{"type": "outcome", "game": 1, "winner": "X"}`: the game is over. The
+//!   winner is `null` for a draw.
+//! - `{"type": "error", "message": "Occupied"}`
+
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tungstenite::Message;
+
+use super::{
+    lobby::{Action, Event, Lobby, Notice},
+    GameInfo, GameOutcome,
+};
+
+/// How often each connection checks for messages to send while it's waiting
+/// for the client.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Create {
+        dim: usize,
+        players: u32,
+    },
+    List,
+    Join {
+        #[serde(default)]
+        game: Option<u32>,
+    },
+    Rejoin {
+        game: u32,
+        #[serde(with = "hex")]
+        token: u64,
+    },
+    Spectate {
+        game: u32,
+    },
+    Place {
+        coords: Vec<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Created {
+        game: u32,
+    },
+    Games {
+        games: Vec<GameInfo>,
+    },
+    Joined {
+        game: u32,
+        player: char,
+        dim: usize,
+        players: u32,
+        #[serde(with = "hex")]
+        token: u64,
+    },
+    Spectating {
+        game: u32,
+        dim: usize,
+        players: u32,
+    },
+    Update {
+        game: u32,
+        moves: Vec<Move>,
+        current_player: char,
+    },
+    Outcome {
+        game: u32,
+        winner: Option<char>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub player: char,
+    pub coords: Vec<usize>,
+}
+
+pub struct WebServer {
+    listener: TcpListener,
+    lobby: Lobby,
+}
+
+impl WebServer {
+    /// Listens on `address`, closing games that have been idle for
+    /// `idle_timeout`.
+    pub fn bind(address: impl ToSocketAddrs, idle_timeout: Duration) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            lobby: Lobby::new(idle_timeout),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections and hosts their games. Connections that fail
+    /// before they're set up are logged and skipped.
+    pub fn run(self) -> io::Result<()> {
+        let (sender, events) = mpsc::channel();
+        let lobby = self.lobby;
+        thread::spawn(move || lobby.run(events));
+
+        for (id, stream) in self.listener.incoming().enumerate() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Couldn't accept a connection: {e}");
+                    continue;
+                }
+            };
+            let events = sender.clone();
+            thread::spawn(move || connect(stream, id, events));
+        }
+        Ok(())
+    }
+}
+
+/// Talks to one client, passing its requests on to the lobby and sending
+/// back whatever the lobby has for it.
+fn connect(stream: TcpStream, id: usize, events: Sender<Event>) {
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+
+    let (sender, notices) = mpsc::channel();
+    if events.send(Event::Connected(id, sender)).is_err() {
+        return;
+    }
+
+    'connection: loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let event = match serde_json::from_str::<Request>(&text) {
+                    Ok(request) => Event::Action(id, request.into()),
+                    Err(e) => Event::Invalid(id, format!("Invalid request: {e}")),
+                };
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => (),
+            // Nothing from the client yet
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => break,
+        }
+
+        while let Ok(notice) = notices.try_recv() {
+            if !send(&mut socket, &notice.into()) {
+                break 'connection;
+            }
+        }
+    }
+
+    let _ = events.send(Event::Disconnected(id));
+}
+
+/// Returns whether or not the client is still there.
+fn send(socket: &mut tungstenite::WebSocket<TcpStream>, response: &Response) -> bool {
+    let json = serde_json::to_string(response).expect("Responses can be serialized");
+    socket.send(Message::Text(json)).is_ok()
+}
+
+impl From<Request> for Action {
+    fn from(request: Request) -> Self {
+        match request {
+            Request::Create { dim, players } => Action::Create { dim, players },
+            Request::List => Action::List,
+            Request::Join { game } => Action::Join(game),
+            Request::Rejoin { game, token } => Action::Rejoin { game, token },
+            Request::Spectate { game } => Action::Spectate(game),
+            Request::Place { coords } => Action::Place(coords.into()),
+        }
+    }
+}
+
+impl From<Notice> for Response {
+    fn from(notice: Notice) -> Self {
+        match notice {
+            Notice::Created(game) => Response::Created { game },
+            Notice::Games(games) => Response::Games { games },
+            Notice::Joined {
+                game,
+                player,
+                dim,
+                players,
+                token,
+            } => Response::Joined {
+                game,
+                player: player.symbol(),
+                dim,
+                players,
+                token,
+            },
+            Notice::Spectating { game, dim, players } => {
+                Response::Spectating { game, dim, players }
+            }
+            Notice::Update {
+                game,
+                moves,
+                current_player,
+            } => Response::Update {
+                game,
+                moves: moves
+                    .into_iter()
+                    .map(|(coords, player)| Move {
+                        player: player.symbol(),
+                        coords: coords.into(),
+                    })
+                    .collect(),
+                current_player: current_player.symbol(),
+            },
+            Notice::Outcome { game, outcome } => Response::Outcome {
+                game,
+                winner: match outcome {
+                    GameOutcome::Win(player) => Some(player.symbol()),
+                    GameOutcome::Draw => None,
+                },
+            },
+            Notice::Error(message) => Response::Error { message },
+        }
+    }
+}
+
+/// Tokens are strings of hexadecimal, since JavaScript can't represent every
+/// `u64` as a number.
+mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(token: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{token:x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        u64::from_str_radix(&token, 16).map_err(D::Error::custom)
+    }
+}